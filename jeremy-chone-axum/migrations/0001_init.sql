@@ -0,0 +1,16 @@
+-- Initial schema.
+-- NOTE: `{{ID_PK}}` is replaced by the dialect auto-increment primary key
+--       (see `model::store::sql`).
+
+CREATE TABLE ticket (
+  id {{ID_PK}},
+  cid BIGINT NOT NULL, -- creator user_id
+  title TEXT NOT NULL
+);
+
+-- `user` is a reserved word in Postgres.
+CREATE TABLE app_user (
+  id {{ID_PK}},
+  username TEXT NOT NULL UNIQUE,
+  pwd_hash TEXT NOT NULL
+);
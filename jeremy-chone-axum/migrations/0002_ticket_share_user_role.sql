@@ -0,0 +1,9 @@
+-- Ticket ownership / sharing and user roles.
+
+ALTER TABLE app_user ADD COLUMN role TEXT NOT NULL DEFAULT 'member';
+
+CREATE TABLE ticket_share (
+  ticket_id BIGINT NOT NULL,
+  user_id BIGINT NOT NULL,
+  PRIMARY KEY (ticket_id, user_id)
+);
@@ -0,0 +1,6 @@
+-- Revoked auth token ids (logoff), until their latest possible expiration.
+
+CREATE TABLE token_denylist (
+  token_id TEXT PRIMARY KEY,
+  until BIGINT NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Api keys for machine clients (permissions is a json array).
+
+CREATE TABLE api_key (
+  id TEXT PRIMARY KEY,
+  user_id BIGINT NOT NULL,
+  name TEXT NOT NULL,
+  permissions TEXT NOT NULL,
+  secret_hash TEXT NOT NULL,
+  ctime BIGINT NOT NULL
+);
+
+CREATE INDEX api_key_user_id_idx ON api_key (user_id);
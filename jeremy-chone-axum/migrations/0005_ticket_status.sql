@@ -0,0 +1,3 @@
+-- Ticket workflow status (see `TicketStatus`).
+
+ALTER TABLE ticket ADD COLUMN status TEXT NOT NULL DEFAULT 'open';
@@ -0,0 +1,12 @@
+-- Ticket comments (deleted with their ticket).
+
+CREATE TABLE comment (
+  id {{ID_PK}},
+  ticket_id BIGINT NOT NULL,
+  cid BIGINT NOT NULL, -- author user_id
+  body TEXT NOT NULL,
+  ctime BIGINT NOT NULL,
+  mtime BIGINT NOT NULL
+);
+
+CREATE INDEX comment_ticket_id_idx ON comment (ticket_id);
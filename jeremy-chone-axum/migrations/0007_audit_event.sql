@@ -0,0 +1,12 @@
+-- Ticket audit trail (append-only, kept after the ticket is deleted).
+
+CREATE TABLE audit_event (
+  id {{ID_PK}},
+  ticket_id BIGINT NOT NULL,
+  actor_id BIGINT NOT NULL, -- user_id
+  action TEXT NOT NULL,
+  diff TEXT NOT NULL, -- json
+  ctime BIGINT NOT NULL
+);
+
+CREATE INDEX audit_event_ticket_id_idx ON audit_event (ticket_id);
@@ -0,0 +1,5 @@
+-- Ticket soft delete (see `Ticket::deleted_at`), NULL when not in the trash.
+
+ALTER TABLE ticket ADD COLUMN deleted_at BIGINT;
+
+CREATE INDEX ticket_deleted_at_idx ON ticket (deleted_at);
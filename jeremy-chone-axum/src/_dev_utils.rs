@@ -0,0 +1,42 @@
+//! Dev only helpers
+//! (seed the demo users on a fresh store, for local dev and the `tests/` harness)
+
+use tracing::debug;
+
+use crate::{
+    config::Config,
+    model::{
+        user::{Role, UserBmc, UserForCreate},
+        ModelController,
+    },
+    Result,
+};
+
+/// (username, role)
+const DEMO_USERS: &[(&str, Role)] = &[("demo1", Role::Member), ("admin", Role::Admin)];
+
+/// Seed the demo users with the `dev_seed_pwd` password (idempotent, no-op when not set).
+pub async fn init_dev(mc: &ModelController, config: &Config) -> Result<()> {
+    let Some(pwd) = config.dev_seed_pwd.as_deref() else {
+        return Ok(());
+    };
+    debug!("{:<12} - init_dev", "FOR-DEV-ONLY");
+
+    for (username, role) in DEMO_USERS {
+        if UserBmc::first_by_username(mc, username).await?.is_some() {
+            continue;
+        }
+
+        let user = UserBmc::create(
+            mc,
+            UserForCreate {
+                username: username.to_string(),
+                pwd_clear: pwd.to_string(),
+            },
+        )
+        .await?;
+        UserBmc::update_role(mc, user.id, *role).await?;
+    }
+
+    Ok(())
+}
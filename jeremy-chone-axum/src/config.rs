@@ -0,0 +1,457 @@
+//! Service config, loaded once at startup (`Config::load`) and passed down in the app state.
+//!
+//! Layers, the last one wins:
+//! 1. Defaults (below).
+//! 2. The TOML file at `SERVICE_CONFIG_FILE` (optional, e.g., `service.example.toml`).
+//! 3. The `SERVICE_...` env variables (empty values are ignored).
+//!
+//! The file keys map to the env names, e.g., `[token] key` is `SERVICE_TOKEN_KEY`.
+
+use std::{
+    collections::BTreeMap, env, fs, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{log::LogSinkConfig, trace::TraceFormat, web::AUTH_TOKEN, Error, Result};
+
+const CONFIG_FILE_ENV: &str = "SERVICE_CONFIG_FILE";
+
+/// Minimum token key length (HMAC-SHA256 key, at least the 32 bytes hash output).
+const TOKEN_KEY_MIN_LEN: usize = 32;
+
+/// All the config entries (env names), anything else in the config file is an error.
+const CONFIG_NAMES: &[&str] = &[
+    "SERVICE_WEB_BIND_ADDR",
+    "SERVICE_WEB_STATIC_DIR",
+    "SERVICE_WEB_AUTH_COOKIE",
+    "SERVICE_TOKEN_KEY",
+    "SERVICE_TOKEN_DURATION_SEC",
+    "SERVICE_DB_URL",
+    "SERVICE_LOG_SINKS",
+    "SERVICE_LOG_FILE_DIR",
+    "SERVICE_LOG_FILE_MAX_BYTES",
+    "SERVICE_LOG_FILE_MAX_AGE_SEC",
+    "SERVICE_LOG_HTTP_URL",
+    "SERVICE_TRACE_FILTER",
+    "SERVICE_TRACE_FORMAT",
+    "SERVICE_RATE_LOGIN_PER_MIN",
+    "SERVICE_RATE_API_PER_MIN",
+    "SERVICE_LOGIN_LOCKOUT_FAILURES",
+    "SERVICE_LOGIN_LOCKOUT_WINDOW_SEC",
+    "SERVICE_LOGIN_LOCKOUT_SEC",
+    "SERVICE_TICKET_TRASH_RETENTION_SEC",
+    "SERVICE_TICKET_TRASH_PURGE_INTERVAL_SEC",
+    "SERVICE_SHUTDOWN_DRAIN_TIMEOUT_SEC",
+    "SERVICE_DEV_SEED_PWD",
+];
+
+pub struct Config {
+    // -- Web
+    pub bind_addr: SocketAddr,
+    /// Root of the static files (fallback service), nothing outside of it is served.
+    pub web_folder: PathBuf,
+    /// Name of the session cookie.
+    pub auth_cookie: String,
+
+    // -- Crypt
+    pub token_key: Vec<u8>,
+    pub token_duration_sec: u64,
+
+    // -- Db
+    /// `None` for the in-memory mock store.
+    pub db_url: Option<String>,
+
+    // -- Log
+    pub log_sinks: Vec<LogSinkConfig>,
+
+    // -- Trace
+    /// `RUST_LOG` style directives, `RUST_LOG` wins when set.
+    pub trace_filter: String,
+    pub trace_format: TraceFormat,
+
+    // -- Rate Limit
+    /// Login attempts per minute, per client ip and per username.
+    pub rate_login_per_min: u32,
+    /// Requests per minute per client ip on the whole `/api` tree (`None` to disable).
+    pub rate_api_per_min: Option<u32>,
+    /// Failed logins within `login_lockout_window_sec` before the account is locked.
+    pub login_lockout_failures: u32,
+    pub login_lockout_window_sec: u64,
+    pub login_lockout_sec: u64,
+
+    // -- Trash
+    /// Time a deleted ticket stays in the trash before being purged.
+    pub ticket_trash_retention_sec: u64,
+    pub ticket_trash_purge_interval_sec: u64,
+
+    // -- Shutdown
+    /// Max wait for the in-flight requests after the shutdown signal.
+    pub shutdown_drain_timeout_sec: u64,
+
+    // -- Dev
+    /// Password of the seeded demo users (`None` to not seed them).
+    pub dev_seed_pwd: Option<String>,
+}
+
+impl Config {
+    /// Load and validate the config (defaults < config file < env).
+    pub fn load() -> Result<Config> {
+        let file = match env::var(CONFIG_FILE_ENV).ok().filter(|v| !v.is_empty()) {
+            Some(path) => read_config_file(&path)?,
+            None => BTreeMap::new(),
+        };
+        let env = env::vars()
+            .filter(|(name, _)| name.starts_with("SERVICE_"))
+            .collect();
+
+        Self::from_source(&Source { file, env })
+    }
+
+    fn from_source(src: &Source) -> Result<Config> {
+        let config = Config {
+            // -- Web
+            bind_addr: src.parse_or(
+                "SERVICE_WEB_BIND_ADDR",
+                SocketAddr::from(([127, 0, 0, 1], 3089)),
+            )?,
+            web_folder: PathBuf::from(src.get_or("SERVICE_WEB_STATIC_DIR", "web-folder/")),
+            auth_cookie: src.get_or("SERVICE_WEB_AUTH_COOKIE", AUTH_TOKEN),
+
+            // -- Crypt
+            token_key: src.b64u_as_u8s("SERVICE_TOKEN_KEY")?,
+            token_duration_sec: src.parse("SERVICE_TOKEN_DURATION_SEC")?,
+
+            // -- Db
+            db_url: src.get_opt("SERVICE_DB_URL"),
+
+            // -- Log
+            log_sinks: load_log_sinks(src)?,
+
+            // -- Trace
+            trace_filter: src.get_or("SERVICE_TRACE_FILTER", "info"),
+            trace_format: src.parse_or("SERVICE_TRACE_FORMAT", TraceFormat::Pretty)?,
+
+            // -- Rate Limit
+            rate_login_per_min: src.parse_or("SERVICE_RATE_LOGIN_PER_MIN", 10)?,
+            rate_api_per_min: src.parse_opt("SERVICE_RATE_API_PER_MIN")?,
+            login_lockout_failures: src.parse_or("SERVICE_LOGIN_LOCKOUT_FAILURES", 5)?,
+            login_lockout_window_sec: src.parse_or("SERVICE_LOGIN_LOCKOUT_WINDOW_SEC", 900)?,
+            login_lockout_sec: src.parse_or("SERVICE_LOGIN_LOCKOUT_SEC", 900)?,
+
+            // -- Trash
+            ticket_trash_retention_sec: src
+                .parse_or("SERVICE_TICKET_TRASH_RETENTION_SEC", 30 * 24 * 3600)?,
+            ticket_trash_purge_interval_sec: src
+                .parse_or("SERVICE_TICKET_TRASH_PURGE_INTERVAL_SEC", 3600)?,
+
+            // -- Shutdown
+            shutdown_drain_timeout_sec: src.parse_or("SERVICE_SHUTDOWN_DRAIN_TIMEOUT_SEC", 30)?,
+
+            // -- Dev
+            dev_seed_pwd: src.get_opt("SERVICE_DEV_SEED_PWD"),
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// The defaults with a token key, for the unit tests (no env nor config file).
+    #[cfg(test)]
+    pub(crate) fn for_test() -> Config {
+        let env = [
+            ("SERVICE_TOKEN_KEY", tests::TOKEN_KEY),
+            ("SERVICE_TOKEN_DURATION_SEC", "1800"),
+        ];
+        let env = env
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let src = Source {
+            file: BTreeMap::new(),
+            env,
+        };
+
+        Self::from_source(&src).expect("test config")
+    }
+
+    /// Checks beyond the value formats.
+    fn validate(&self) -> Result<()> {
+        if !self.web_folder.is_dir() {
+            return Err(Error::ConfigInvalid {
+                name: "SERVICE_WEB_STATIC_DIR",
+                reason: "not a directory",
+            });
+        }
+
+        let cookie_name_valid = !self.auth_cookie.is_empty()
+            && self
+                .auth_cookie
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !cookie_name_valid {
+            return Err(Error::ConfigInvalid {
+                name: "SERVICE_WEB_AUTH_COOKIE",
+                reason: "must be [A-Za-z0-9-_.]+",
+            });
+        }
+
+        if self.token_key.len() < TOKEN_KEY_MIN_LEN {
+            return Err(Error::ConfigInvalid {
+                name: "SERVICE_TOKEN_KEY",
+                reason: "must be at least 32 bytes",
+            });
+        }
+        if self.token_duration_sec == 0 {
+            return Err(Error::ConfigInvalid {
+                name: "SERVICE_TOKEN_DURATION_SEC",
+                reason: "must be > 0",
+            });
+        }
+
+        if self.ticket_trash_purge_interval_sec == 0 {
+            return Err(Error::ConfigInvalid {
+                name: "SERVICE_TICKET_TRASH_PURGE_INTERVAL_SEC",
+                reason: "must be > 0",
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// `SERVICE_LOG_SINKS` is a comma separated list (or a TOML array) of `stdout`, `file`, `http`
+/// (default `stdout`), each with its own `SERVICE_LOG_...` settings.
+fn load_log_sinks(src: &Source) -> Result<Vec<LogSinkConfig>> {
+    let names = src.get_or("SERVICE_LOG_SINKS", "stdout");
+
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| match name {
+            "stdout" => Ok(LogSinkConfig::Stdout),
+            "file" => Ok(LogSinkConfig::File {
+                dir: PathBuf::from(src.get("SERVICE_LOG_FILE_DIR")?),
+                max_bytes: src.parse_or("SERVICE_LOG_FILE_MAX_BYTES", 10 * 1024 * 1024)?,
+                max_age: Duration::from_secs(
+                    src.parse_or("SERVICE_LOG_FILE_MAX_AGE_SEC", 24 * 3600)?,
+                ),
+            }),
+            "http" => Ok(LogSinkConfig::Http {
+                url: src.get("SERVICE_LOG_HTTP_URL")?,
+            }),
+            _ => Err(Error::ConfigWrongFormat("SERVICE_LOG_SINKS")),
+        })
+        .collect()
+}
+
+// region:    --- Config File
+fn read_config_file(path: &str) -> Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path).map_err(|ex| Error::ConfigFileRead {
+        path: path.to_string(),
+        cause: ex.to_string(),
+    })?;
+
+    parse_config_file(&content).map_err(|cause| Error::ConfigFileParse {
+        path: path.to_string(),
+        cause,
+    })
+}
+
+/// Flattens the `[section] key = value` entries to their env names
+/// (arrays are joined with `,`).
+fn parse_config_file(content: &str) -> core::result::Result<BTreeMap<String, String>, String> {
+    let root: toml::Table = content
+        .parse()
+        .map_err(|ex: toml::de::Error| ex.message().to_string())?;
+
+    let mut entries = BTreeMap::new();
+    for (section, table) in root {
+        let toml::Value::Table(table) = table else {
+            return Err(format!("'{section}' must be a [section]"));
+        };
+
+        for (key, value) in table {
+            let name = format!("SERVICE_{section}_{key}").to_uppercase();
+            if !CONFIG_NAMES.contains(&name.as_str()) {
+                return Err(format!("unknown key '{section}.{key}'"));
+            }
+
+            let value = match value {
+                toml::Value::Array(items) => items
+                    .iter()
+                    .map(config_file_scalar)
+                    .collect::<Option<Vec<_>>>()
+                    .map(|items| items.join(",")),
+                value => config_file_scalar(&value),
+            }
+            .ok_or_else(|| format!("'{section}.{key}' must be a string, number or boolean"))?;
+
+            entries.insert(name, value);
+        }
+    }
+
+    Ok(entries)
+}
+
+fn config_file_scalar(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(v) => Some(v.clone()),
+        toml::Value::Integer(v) => Some(v.to_string()),
+        toml::Value::Float(v) => Some(v.to_string()),
+        toml::Value::Boolean(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+// endregion: --- Config File
+
+// region:    --- Source Helpers
+/// The config values by env name, the env wins over the file.
+struct Source {
+    file: BTreeMap<String, String>,
+    env: BTreeMap<String, String>,
+}
+
+impl Source {
+    fn get_opt(&self, name: &'static str) -> Option<String> {
+        [&self.env, &self.file]
+            .into_iter()
+            .filter_map(|layer| layer.get(name))
+            .find(|v| !v.is_empty())
+            .cloned()
+    }
+
+    fn get(&self, name: &'static str) -> Result<String> {
+        self.get_opt(name).ok_or(Error::ConfigMissing(name))
+    }
+
+    fn get_or(&self, name: &'static str, default: &str) -> String {
+        self.get_opt(name).unwrap_or_else(|| default.to_string())
+    }
+
+    fn parse<T: FromStr>(&self, name: &'static str) -> Result<T> {
+        let val = self.get(name)?;
+        val.parse::<T>().map_err(|_| Error::ConfigWrongFormat(name))
+    }
+
+    fn parse_or<T: FromStr>(&self, name: &'static str, default: T) -> Result<T> {
+        Ok(self.parse_opt(name)?.unwrap_or(default))
+    }
+
+    fn parse_opt<T: FromStr>(&self, name: &'static str) -> Result<Option<T>> {
+        self.get_opt(name)
+            .map(|val| val.parse::<T>().map_err(|_| Error::ConfigWrongFormat(name)))
+            .transpose()
+    }
+
+    fn b64u_as_u8s(&self, name: &'static str) -> Result<Vec<u8>> {
+        URL_SAFE_NO_PAD
+            .decode(self.get(name)?)
+            .map_err(|_| Error::ConfigWrongFormat(name))
+    }
+}
+// endregion: --- Source Helpers
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) const TOKEN_KEY: &str =
+        "_ug5r7Z7geiwcL0N13dcDxZ1l-lcIu8INtk-jp4xu3tdzZg9jBnIVZZmKOmFd-3PQcGBsUo2cmJzpj5_D00Zng";
+
+    fn source(file: &str, env: &[(&str, &str)]) -> Source {
+        Source {
+            file: parse_config_file(file).unwrap(),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_config_layers() -> Result<()> {
+        let file = format!(
+            r#"
+            [web]
+            bind_addr = "0.0.0.0:8080"
+            auth_cookie = "sid"
+
+            [token]
+            key = "{TOKEN_KEY}"
+            duration_sec = 600
+
+            [log]
+            sinks = ["stdout", "http"]
+            http_url = "http://localhost:8089/logs"
+            "#
+        );
+        // Env wins, empty env values are ignored.
+        let src = source(
+            &file,
+            &[
+                ("SERVICE_TOKEN_DURATION_SEC", "60"),
+                ("SERVICE_WEB_AUTH_COOKIE", ""),
+            ],
+        );
+
+        let config = Config::from_source(&src)?;
+
+        assert_eq!(config.bind_addr, SocketAddr::from(([0, 0, 0, 0], 8080)));
+        assert_eq!(config.auth_cookie, "sid");
+        assert_eq!(config.token_duration_sec, 60);
+        assert_eq!(config.log_sinks.len(), 2);
+        // Defaults.
+        assert_eq!(config.web_folder, PathBuf::from("web-folder/"));
+        assert_eq!(config.rate_login_per_min, 10);
+        assert!(config.db_url.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_example_file() {
+        let entries = parse_config_file(include_str!("../service.example.toml")).unwrap();
+        assert_eq!(
+            entries.get("SERVICE_LOG_SINKS").map(String::as_str),
+            Some("stdout")
+        );
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let key = ("SERVICE_TOKEN_KEY", TOKEN_KEY);
+        let duration = ("SERVICE_TOKEN_DURATION_SEC", "60");
+
+        let res = Config::from_source(&source("", &[duration]));
+        assert!(matches!(
+            res,
+            Err(Error::ConfigMissing("SERVICE_TOKEN_KEY"))
+        ));
+
+        let res = Config::from_source(&source(
+            "",
+            &[key, duration, ("SERVICE_WEB_BIND_ADDR", "localhost")],
+        ));
+        assert!(matches!(
+            res,
+            Err(Error::ConfigWrongFormat("SERVICE_WEB_BIND_ADDR"))
+        ));
+
+        let res = Config::from_source(&source("", &[("SERVICE_TOKEN_KEY", "c2hvcnQ"), duration]));
+        assert!(matches!(
+            res,
+            Err(Error::ConfigInvalid {
+                name: "SERVICE_TOKEN_KEY",
+                ..
+            })
+        ));
+
+        let res = parse_config_file("[web]\nbind = \"0.0.0.0:80\"");
+        assert_eq!(res, Err("unknown key 'web.bind'".to_string()));
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,112 @@
+use std::{fmt::Display, str::FromStr};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use lazy_regex::regex_captures;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+// region:    --- ApiKeyClear Type
+/// Clear api key, only known by the client (only the hash of the secret is stored).
+///
+/// String format: `ak-[key-id].[secret]`
+/// - key-id: public id of the key (32 hex chars), used for the lookup.
+/// - secret: base64url of 32 random bytes.
+#[derive(Debug)]
+pub struct ApiKeyClear {
+    pub key_id: String,
+    pub secret_b64u: String,
+}
+
+impl ApiKeyClear {
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+
+        Self {
+            key_id: Uuid::new_v4().simple().to_string(),
+            secret_b64u: URL_SAFE_NO_PAD.encode(secret),
+        }
+    }
+
+    /// Hash of the secret to be stored.
+    ///
+    /// NOTE: The secret is random (not a password), so a fast hash is enough.
+    pub fn secret_hash(&self) -> String {
+        hash_secret(&self.secret_b64u)
+    }
+
+    /// Constant-time comparison of the hashes.
+    pub fn validate(&self, secret_hash: &str) -> Result<()> {
+        let secret_hash = URL_SAFE_NO_PAD
+            .decode(secret_hash)
+            .map_err(|_| Error::AuthFailApiKeyInvalid)?;
+        let hash = Sha256::digest(self.secret_b64u.as_bytes());
+
+        if bool::from(hash.as_slice().ct_eq(&secret_hash)) {
+            Ok(())
+        } else {
+            Err(Error::AuthFailApiKeyInvalid)
+        }
+    }
+}
+
+impl FromStr for ApiKeyClear {
+    type Err = Error;
+
+    fn from_str(api_key_str: &str) -> Result<Self> {
+        let (_whole, key_id, secret_b64u) = regex_captures!(
+            r#"^ak-([0-9a-f]{32})\.([A-Za-z0-9_-]{43})$"#, // a literal regex
+            api_key_str
+        )
+        .ok_or(Error::AuthFailApiKeyWrongFormat)?;
+
+        Ok(Self {
+            key_id: key_id.to_string(),
+            secret_b64u: secret_b64u.to_string(),
+        })
+    }
+}
+
+impl Display for ApiKeyClear {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ak-{}.{}", self.key_id, self.secret_b64u)
+    }
+}
+// endregion: --- ApiKeyClear Type
+
+fn hash_secret(secret_b64u: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(secret_b64u.as_bytes()))
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_display_parse_validate() -> Result<()> {
+        let api_key = ApiKeyClear::generate();
+        let secret_hash = api_key.secret_hash();
+
+        let parsed: ApiKeyClear = api_key.to_string().parse()?;
+        assert_eq!(parsed.key_id, api_key.key_id);
+        parsed.validate(&secret_hash)?;
+
+        let other = ApiKeyClear::generate();
+        assert!(matches!(
+            other.validate(&secret_hash),
+            Err(Error::AuthFailApiKeyInvalid)
+        ));
+        assert!(matches!(
+            "ak-123.abc".parse::<ApiKeyClear>(),
+            Err(Error::AuthFailApiKeyWrongFormat)
+        ));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,6 @@
+//! Crypt helpers
+//! (token signature, password and api key hashing)
+
+pub mod api_key;
+pub mod pwd;
+pub mod token;
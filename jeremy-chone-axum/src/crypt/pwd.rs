@@ -0,0 +1,34 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+};
+
+use crate::{Error, Result};
+
+/// Hash a clear password with Argon2id and a random salt.
+/// Returns the PHC string (algorithm, params and salt are embedded).
+///
+/// NOTE: Hashing is slow by design, so it runs on the blocking thread pool.
+pub async fn hash_pwd(pwd_clear: String) -> Result<String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(pwd_clear.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|_| Error::PwdHashFail)
+    })
+    .await
+    .map_err(|_| Error::PwdHashFail)?
+}
+
+/// Validate a clear password against a PHC string from `hash_pwd`.
+pub async fn validate_pwd(pwd_clear: String, pwd_hash: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        let pwd_hash = PasswordHash::new(&pwd_hash).map_err(|_| Error::PwdHashFail)?;
+        Argon2::default()
+            .verify_password(pwd_clear.as_bytes(), &pwd_hash)
+            .map_err(|_| Error::PwdNotMatching)
+    })
+    .await
+    .map_err(|_| Error::PwdHashFail)?
+}
@@ -0,0 +1,180 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use lazy_regex::regex_captures;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+// region:    --- Token Type
+/// String format: `user-[user-id].[token-id].[expiration].[signature]`
+/// - token-id: session id (32 hex chars), kept when the token is refreshed,
+///   so revoking it ends the whole session.
+/// - expiration: unix timestamp in seconds.
+/// - signature: base64url of HMAC-SHA256(`user-[user-id].[token-id].[expiration]`, key).
+#[derive(Debug)]
+pub struct Token {
+    pub user_id: u64,
+    pub token_id: String,
+    pub exp: u64,
+    pub sign_b64u: String,
+}
+
+impl FromStr for Token {
+    type Err = Error;
+
+    fn from_str(token_str: &str) -> Result<Self> {
+        let (_whole, user_id, token_id, exp, sign_b64u) = regex_captures!(
+            r#"^user-(\d+)\.([0-9a-f]{32})\.(\d+)\.([A-Za-z0-9_-]+)$"#, // a literal regex
+            token_str
+        )
+        .ok_or(Error::AuthFailTokenWrongFormat)?;
+
+        let user_id = user_id
+            .parse::<u64>()
+            .map_err(|_| Error::AuthFailTokenWrongFormat)?;
+        let exp = exp
+            .parse::<u64>()
+            .map_err(|_| Error::AuthFailTokenWrongFormat)?;
+
+        Ok(Self {
+            user_id,
+            token_id: token_id.to_string(),
+            exp,
+            sign_b64u: sign_b64u.to_string(),
+        })
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "user-{}.{}.{}.{}",
+            self.user_id, self.token_id, self.exp, self.sign_b64u
+        )
+    }
+}
+// endregion: --- Token Type
+
+// region:    --- Token Gen and Validation
+/// New session token (new token id).
+pub fn generate_token(user_id: u64, duration_sec: u64, key: &[u8]) -> Result<Token> {
+    let token_id = Uuid::new_v4().simple().to_string();
+    sign_token(user_id, token_id, duration_sec, key)
+}
+
+/// Same session (same token id) with a new expiration, for the sliding session.
+/// The `token` must have been validated.
+pub fn refresh_token(token: &Token, duration_sec: u64, key: &[u8]) -> Result<Token> {
+    sign_token(token.user_id, token.token_id.clone(), duration_sec, key)
+}
+
+fn sign_token(user_id: u64, token_id: String, duration_sec: u64, key: &[u8]) -> Result<Token> {
+    let exp = now_unix_sec() + duration_sec;
+    let sign_b64u = token_sign_into_b64u(user_id, &token_id, exp, key)?;
+
+    Ok(Token {
+        user_id,
+        token_id,
+        exp,
+        sign_b64u,
+    })
+}
+
+pub fn validate_token(token: &Token, key: &[u8]) -> Result<()> {
+    // -- Validate signature (constant-time comparison).
+    let sign = URL_SAFE_NO_PAD
+        .decode(&token.sign_b64u)
+        .map_err(|_| Error::AuthFailSignatureInvalid)?;
+    token_mac(token.user_id, &token.token_id, token.exp, key)?
+        .verify_slice(&sign)
+        .map_err(|_| Error::AuthFailSignatureInvalid)?;
+
+    // -- Validate expiration.
+    if token.exp <= now_unix_sec() {
+        return Err(Error::AuthFailExpired);
+    }
+
+    Ok(())
+}
+
+fn token_sign_into_b64u(user_id: u64, token_id: &str, exp: u64, key: &[u8]) -> Result<String> {
+    let sign = token_mac(user_id, token_id, exp, key)?
+        .finalize()
+        .into_bytes();
+
+    Ok(URL_SAFE_NO_PAD.encode(sign))
+}
+
+fn token_mac(user_id: u64, token_id: &str, exp: u64, key: &[u8]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::TokenKeyInvalid)?;
+    mac.update(format!("user-{user_id}.{token_id}.{exp}").as_bytes());
+
+    Ok(mac)
+}
+
+pub fn now_unix_sec() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+// endregion: --- Token Gen and Validation
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"test-key-0123456789";
+
+    #[test]
+    fn test_token_display_parse_validate() -> Result<()> {
+        let token = generate_token(7, 60, KEY)?;
+        let parsed: Token = token.to_string().parse()?;
+
+        assert_eq!(parsed.user_id, 7);
+        assert_eq!(parsed.token_id, token.token_id);
+        validate_token(&parsed, KEY)?;
+        assert!(matches!(
+            validate_token(&parsed, b"other-key"),
+            Err(Error::AuthFailSignatureInvalid)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_refresh_keeps_token_id() -> Result<()> {
+        let token = generate_token(7, 0, KEY)?;
+        assert!(matches!(
+            validate_token(&token, KEY),
+            Err(Error::AuthFailExpired)
+        ));
+
+        let refreshed = refresh_token(&token, 60, KEY)?;
+        assert_eq!(refreshed.token_id, token.token_id);
+        assert!(refreshed.exp > token.exp);
+        validate_token(&refreshed, KEY)?;
+
+        // A tampered token id invalidates the signature.
+        let tampered = Token {
+            token_id: Uuid::new_v4().simple().to_string(),
+            ..refreshed
+        };
+        assert!(matches!(
+            validate_token(&tampered, KEY),
+            Err(Error::AuthFailSignatureInvalid)
+        ));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -1,18 +1,60 @@
+use std::collections::HashSet;
+
+use crate::{
+    model::user::{Permission, Role},
+    web::mw_req_id::ReqId,
+    Error, Result,
+};
+
 #[derive(Clone, Debug)]
 pub struct Ctx {
+    req_id: ReqId,
     user_id: u64,
+    role: Role,
+    permissions: HashSet<Permission>,
 }
 
 // Constructor
 impl Ctx {
-    pub fn new(user_id: u64) -> Self {
-        Self { user_id }
+    pub fn new(
+        req_id: ReqId,
+        user_id: u64,
+        role: Role,
+        permissions: impl IntoIterator<Item = Permission>,
+    ) -> Self {
+        Self {
+            req_id,
+            user_id,
+            role,
+            permissions: permissions.into_iter().collect(),
+        }
     }
 }
 
 // Property Accessors.
 impl Ctx {
+    pub fn req_id(&self) -> &ReqId {
+        &self.req_id
+    }
+
     pub fn user_id(&self) -> u64 {
         self.user_id
     }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Fails with `Error::PermissionDenied` if the permission is missing.
+    pub fn require(&self, permission: Permission) -> Result<()> {
+        if self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied { permission })
+        }
+    }
 }
@@ -2,19 +2,125 @@ use std::fmt::Display;
 
 use axum::{http::StatusCode, response::IntoResponse};
 use serde::Serialize;
+use tracing::debug;
+
+use crate::model::{user::Permission, TicketStatus};
 
 #[derive(Clone, Debug, Serialize, strum_macros::AsRefStr)]
 #[serde(tag = "type", content = "data")]
 pub enum Error {
     LoginFail,
 
+    // -- Config errors.
+    ConfigMissing(&'static str),
+    ConfigWrongFormat(&'static str),
+    ConfigInvalid {
+        name: &'static str,
+        reason: &'static str,
+    },
+    ConfigFileRead {
+        path: String,
+        cause: String,
+    },
+    ConfigFileParse {
+        path: String,
+        cause: String,
+    },
+
+    // -- Crypt errors.
+    TokenKeyInvalid,
+    PwdHashFail,
+    PwdNotMatching,
+
+    // -- Log errors.
+    LogTimestampFormatFail,
+    LogLineSerializeFail,
+    LogSinkIo(String),
+    LogSinkHttp(String),
+    LogTaskGone,
+
+    // -- Trace errors.
+    TraceInitFail(String),
+
+    // -- Server errors.
+    ServerBindFail {
+        addr: String,
+        cause: String,
+    },
+    ServerSignalFail(String),
+
+    // -- Store errors.
+    StoreUnsupportedDbUrl,
+    StoreConnectFail(String),
+    StoreMigrationFail {
+        version: i64,
+        cause: String,
+    },
+    StoreSqlFail(String),
+    StoreLockPoisoned,
+
+    // -- Rpc errors.
+    RpcParseError,
+    RpcInvalidRequest,
+    RpcMethodUnknown(String),
+    RpcMissingParams {
+        rpc_method: String,
+    },
+    RpcFailJsonParams {
+        rpc_method: String,
+    },
+
     // -- Model errors.
-    TicketDeleteFailIdNotFound { id: u64 },
+    TicketNotFound {
+        id: u64,
+    },
+    TicketStatusInvalid(String),
+    TicketTransitionInvalid {
+        id: u64,
+        from: TicketStatus,
+        to: TicketStatus,
+    },
+    CommentNotFound {
+        id: u64,
+    },
+    CommentInvalidParams,
+    UserNotFound {
+        id: u64,
+    },
+    UserAlreadyExists {
+        username: String,
+    },
+    UserInvalidParams,
+    UserRoleInvalid(String),
+    ApiKeyNotFound {
+        id: String,
+    },
+    ApiKeyInvalidParams,
+    AccessDenied,
+    PermissionDenied {
+        permission: Permission,
+    },
 
     // -- Auth errors.
     AuthFailNoAuthTokenCookie,
     AuthFailTokenWrongFormat,
+    AuthFailSignatureInvalid,
+    AuthFailExpired,
+    AuthFailTokenRevoked,
+    AuthFailApiKeyWrongFormat,
+    AuthFailApiKeyInvalid,
+    AuthFailUserNotFound,
     AuthFailCtxNotInRequestExt,
+
+    // -- Web errors.
+    ReqIdNotInRequestExt,
+    RateLimited {
+        retry_after_sec: u64,
+    },
+    LoginLocked {
+        retry_after_sec: u64,
+    },
+    RateLimitLockPoisoned,
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -25,11 +131,38 @@ impl Display for Error {
         write!(f, "{self:?}")
     }
 }
+
+impl std::error::Error for Error {}
 // endregion: --- Error Boilerplate
 
+impl Error {
+    /// For the `Retry-After` response header.
+    pub fn retry_after_sec(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited { retry_after_sec } | Self::LoginLocked { retry_after_sec } => {
+                Some(*retry_after_sec)
+            }
+            _ => None,
+        }
+    }
+
+    /// The token of the auth cookie is rejected (and the cookie removed).
+    /// Not for the other errors, e.g., the store is unreachable.
+    pub fn is_auth_token_fail(&self) -> bool {
+        matches!(
+            self,
+            Self::AuthFailTokenWrongFormat
+                | Self::AuthFailSignatureInvalid
+                | Self::AuthFailExpired
+                | Self::AuthFailTokenRevoked
+                | Self::AuthFailUserNotFound
+        )
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
-        println!("->> {:<12} - {self:?}", "INTO_RES");
+        debug!("{:<12} - {self:?}", "INTO_RES");
 
         // region:    --- 下面实现了 client error 的处理, 因此这部分不需要了
 
@@ -55,18 +188,65 @@ impl Error {
     pub fn client_status_and_error(&self) -> (StatusCode, ClientError) {
         match self {
             // -- Login.
-            Self::LoginFail => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+            Self::LoginFail | Self::PwdNotMatching => {
+                (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL)
+            }
 
             // -- Auth.
             Self::AuthFailNoAuthTokenCookie
             | Self::AuthFailTokenWrongFormat
+            | Self::AuthFailSignatureInvalid
+            | Self::AuthFailExpired
+            | Self::AuthFailTokenRevoked
+            | Self::AuthFailApiKeyWrongFormat
+            | Self::AuthFailApiKeyInvalid
+            | Self::AuthFailUserNotFound
             | Self::AuthFailCtxNotInRequestExt => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
 
             // -- Model.
-            Self::TicketDeleteFailIdNotFound { .. } => {
+            Self::AccessDenied | Self::PermissionDenied { .. } => {
+                (StatusCode::FORBIDDEN, ClientError::ACCESS_DENIED)
+            }
+            Self::TicketNotFound { .. }
+            | Self::UserNotFound { .. }
+            | Self::CommentNotFound { .. }
+            | Self::ApiKeyNotFound { .. } => (StatusCode::NOT_FOUND, ClientError::ENTITY_NOT_FOUND),
+            Self::UserAlreadyExists { .. }
+            | Self::UserInvalidParams
+            | Self::UserRoleInvalid(_)
+            | Self::TicketStatusInvalid(_)
+            | Self::CommentInvalidParams
+            | Self::ApiKeyInvalidParams => (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS),
+
+            Self::TicketTransitionInvalid { .. } => {
+                (StatusCode::CONFLICT, ClientError::INVALID_TRANSITION)
+            }
+
+            // -- Rpc.
+            Self::RpcParseError => (StatusCode::BAD_REQUEST, ClientError::RPC_PARSE_ERROR),
+            Self::RpcInvalidRequest => (StatusCode::BAD_REQUEST, ClientError::RPC_INVALID_REQUEST),
+            Self::RpcMethodUnknown(_) => {
+                (StatusCode::BAD_REQUEST, ClientError::RPC_METHOD_NOT_FOUND)
+            }
+            Self::RpcMissingParams { .. } | Self::RpcFailJsonParams { .. } => {
                 (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS)
             }
 
+            // -- Rate Limit.
+            Self::RateLimited { .. } | Self::LoginLocked { .. } => {
+                (StatusCode::TOO_MANY_REQUESTS, ClientError::RATE_LIMITED)
+            }
+
+            // -- Store.
+            Self::StoreUnsupportedDbUrl
+            | Self::StoreConnectFail(_)
+            | Self::StoreMigrationFail { .. }
+            | Self::StoreSqlFail(_)
+            | Self::StoreLockPoisoned => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ClientError::SERVICE_ERROR,
+            ),
+
             // -- Fallback. (虽然现在无法reach到这里, 但是为了保险起见, 还是写上)
             #[allow(unreachable_patterns)] // 加上这个就不warning了
             _ => (
@@ -78,10 +258,37 @@ impl Error {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, strum_macros::AsRefStr)]
+#[derive(Clone, Copy, Debug, Serialize, strum_macros::AsRefStr, utoipa::ToSchema)]
 pub enum ClientError {
     LOGIN_FAIL,
     NO_AUTH,
+    ACCESS_DENIED,
     INVALID_PARAMS,
+    ENTITY_NOT_FOUND,
+    INVALID_TRANSITION,
+    RPC_PARSE_ERROR,
+    RPC_INVALID_REQUEST,
+    RPC_METHOD_NOT_FOUND,
+    RATE_LIMITED,
     SERVICE_ERROR,
 }
+
+impl ClientError {
+    /// JSON-RPC 2.0 error code (see `web::routes_rpc`).
+    pub fn rpc_code(&self) -> i64 {
+        match self {
+            Self::RPC_PARSE_ERROR => -32700,
+            Self::RPC_INVALID_REQUEST => -32600,
+            Self::RPC_METHOD_NOT_FOUND => -32601,
+            Self::INVALID_PARAMS => -32602,
+            Self::SERVICE_ERROR => -32603,
+            // -- Implementation-defined server errors (-32000 to -32099).
+            Self::LOGIN_FAIL => -32000,
+            Self::NO_AUTH => -32001,
+            Self::ACCESS_DENIED => -32003,
+            Self::ENTITY_NOT_FOUND => -32004,
+            Self::INVALID_TRANSITION => -32009,
+            Self::RATE_LIMITED => -32029,
+        }
+    }
+}
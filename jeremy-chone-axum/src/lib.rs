@@ -0,0 +1,214 @@
+//! Ticket service library
+//! (the binary in `main.rs` loads the config and serves `routes_all`)
+
+use std::sync::Arc;
+
+use crate::{
+    config::Config,
+    log::{log_request, RequestLogger},
+    model::ModelController,
+    web::{
+        mw_metrics::Metrics,
+        mw_rate_limit::RateLimiter,
+        mw_req_id::ReqId,
+        routes_openapi::{ErrorBody, ErrorDetail},
+        routes_rpc::RpcInfo,
+        AppState,
+    },
+};
+
+pub use self::error::{Error, Result};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, Method, StatusCode, Uri},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use ctx::Ctx;
+use serde::Deserialize;
+use serde_json::json;
+use tower::ServiceBuilder;
+use tower_cookies::CookieManagerLayer;
+use tracing::debug;
+
+pub mod _dev_utils;
+pub mod config;
+mod crypt;
+mod ctx;
+mod error;
+pub mod log;
+pub mod model;
+pub mod trace;
+pub mod web;
+
+/// The full app router (all routes and layers).
+///
+/// NOTE: Serve with `into_make_service_with_connect_info::<SocketAddr>()`
+///       (per client ip rate limits).
+pub fn routes_all(
+    config: Arc<Config>,
+    mc: ModelController,
+    logger: RequestLogger,
+    metrics: Metrics,
+) -> Router {
+    // 这个中间件仅作用于 routes_apis
+    let routes_apis = web::routes_tickets::routes(mc.clone())
+        .merge(web::routes_ticket_events::routes(mc.clone()))
+        .merge(web::routes_admin::routes(mc.clone()))
+        .merge(web::routes_api_keys::routes(mc.clone()))
+        .route_layer(middleware::from_fn(web::mw_auth::mw_require_auth))
+        // The rpc handler resolves the auth error itself, to answer with a JSON-RPC error.
+        .merge(web::routes_rpc::routes(mc.clone()));
+
+    // Optional rate limit (per client ip) on the whole /api tree.
+    let routes_apis = match config.rate_api_per_min {
+        Some(per_min) => routes_apis.route_layer(middleware::from_fn_with_state(
+            RateLimiter::per_min(per_min),
+            web::mw_rate_limit::mw_rate_limit,
+        )),
+        None => routes_apis,
+    };
+
+    // merge routes
+    Router::new()
+        .merge(routes_hello())
+        .merge(web::routes_login::routes(mc.clone(), config.clone()))
+        .merge(web::routes_openapi::routes(config.clone()))
+        .merge(web::routes_health::routes(mc.clone(), metrics.clone()))
+        .nest("/api", routes_apis)
+        .layer(
+            // ServiceBuilder 符合直觉, 自上而下, 依次添加中间件, layer是自下而上
+            ServiceBuilder::new()
+                .layer(CookieManagerLayer::new())
+                .layer(middleware::from_fn_with_state(
+                    AppState {
+                        mc: mc.clone(),
+                        config: config.clone(),
+                    },
+                    web::mw_auth::mw_ctx_resolver,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    metrics,
+                    web::mw_metrics::mw_metrics,
+                ))
+                .layer(middleware::map_response_with_state(
+                    logger.clone(),
+                    main_response_mapper,
+                )),
+        )
+        .fallback_service(web::routes_static::routes(&config))
+        // Outermost, so every response (static files included) gets the request id.
+        .layer(middleware::from_fn(web::mw_req_id::mw_req_id))
+}
+
+/// consume Response, return different Response(modify, or not)
+async fn main_response_mapper(
+    State(logger): State<RequestLogger>,
+    req_id: ReqId,
+    ctx: Option<Ctx>,
+    uri: Uri,
+    req_method: Method,
+    res: Response,
+) -> Response {
+    debug!("{:12} - main-response_mapper", "RES_MAPPER");
+    // -- Get the eventual response error.
+    let service_error = res.extensions().get::<Error>();
+    let rpc_info = res.extensions().get::<RpcInfo>();
+
+    // convert the error into a client error.
+    let client_status_error = service_error.map(|se| se.client_status_and_error());
+
+    // -- If client error, build the new response
+    let error_response = client_status_error
+        .as_ref()
+        .map(|(status_code, client_error)| {
+            let client_error_body = match rpc_info {
+                // JSON-RPC 2.0 error object (same client error type and req_uuid in `data`).
+                Some(rpc_info) => json!({
+                    "jsonrpc": "2.0",
+                    "id": rpc_info.id,
+                    "error": {
+                        "code": client_error.rpc_code(),
+                        "message": client_error.as_ref(),
+                        "data": {
+                            "type": client_error.as_ref(),
+                            "req_uuid": req_id.to_string(),
+                        }
+                    }
+                }),
+                None => json!(ErrorBody {
+                    error: ErrorDetail {
+                        r#type: *client_error,
+                        req_uuid: req_id.to_string(),
+                    }
+                }),
+            };
+            debug!("    ->> client_error_body: {client_error_body}");
+
+            // Build the new response from the client_error body.
+            let mut res = (*status_code, Json(client_error_body)).into_response();
+            // For the metrics (see `mw_metrics`).
+            res.extensions_mut().insert(*client_error);
+            if let Some(retry_after_sec) = service_error.and_then(|se| se.retry_after_sec()) {
+                res.headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_sec));
+            }
+            res
+        });
+
+    // Build and log the server log line.
+    // println!("    ->> server log line - {req_id} - Error: {service_error:?}");
+    let _ = log_request(
+        &logger,
+        &req_id,
+        req_method,
+        uri,
+        rpc_info,
+        ctx,
+        service_error,
+    )
+    .await;
+
+    // A JSON-RPC notification gets no response body, not even for an error.
+    if rpc_info.is_some_and(|rpc_info| rpc_info.notification) {
+        let mut res = StatusCode::NO_CONTENT.into_response();
+        if let Some((_, client_error)) = client_status_error {
+            res.extensions_mut().insert(client_error);
+        }
+        return res;
+    }
+
+    error_response.unwrap_or(res)
+}
+
+// region:    --- Routes Hello
+#[derive(Debug, Deserialize)]
+struct HelloParams {
+    name: Option<String>,
+}
+// 模式匹配, 从 Query 中提取参数
+async fn handler_hello(Query(params): Query<HelloParams>) -> impl IntoResponse {
+    debug!("{:12} - handler_hello - {params:?}", "HANDLER");
+    let name = params.name.as_deref().unwrap_or("World!");
+    Html(format!("Hello <strong>{name}</strong>"))
+}
+
+async fn handler_hello2(Path(name): Path<String>) -> impl IntoResponse {
+    debug!("{:12} - handler_hello2 - {name:?}", "HANDLER");
+
+    Html(format!("Hello <strong>{name}</strong>"))
+}
+
+fn routes_hello() -> Router {
+    Router::new()
+        .route(
+            "/hello",
+            // get(|| async { Html("Hello <strong>World!!!</strong>") }),
+            get(handler_hello),
+        )
+        .route("/hello2/:name", get(handler_hello2))
+}
+// endregion: --- Routes Hello
@@ -0,0 +1,320 @@
+//! Request log
+//!
+//! `log_request` never blocks the response path, the log lines are sent to a
+//! background task which batches them and writes them to the configured sinks.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::http::{Method, Uri};
+use serde::Serialize;
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, warn};
+
+use crate::{
+    ctx::Ctx,
+    web::{mw_req_id::ReqId, routes_rpc::RpcInfo},
+    Error, Result,
+};
+
+use self::sink::LogSink;
+pub use self::sink::LogSinkConfig;
+
+mod sink;
+
+const CHANNEL_CAPACITY: usize = 10_000;
+const BATCH_MAX_LINES: usize = 100;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn log_request(
+    logger: &RequestLogger,
+    req_id: &ReqId,
+    req_method: Method,
+    uri: Uri,
+    rpc_info: Option<&RpcInfo>,
+    ctx: Option<Ctx>,
+    service_error: Option<&Error>,
+) -> Result<()> {
+    let timestamp = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .map_err(|_| Error::LogTimestampFormatFail)?;
+
+    let client_error = service_error.map(|se| se.client_status_and_error().1);
+    let error_type = service_error.map(|se| se.as_ref().to_string());
+    let error_data = serde_json::to_value(service_error)
+        .ok()
+        .and_then(|mut v| v.get_mut("data").map(|v| v.take()));
+
+    // Create the RequestLogLine
+    let log_line = RequestLogLine {
+        req_id: req_id.to_string(),
+        timestamp,
+
+        req_path: uri.to_string(),
+        req_method: req_method.to_string(),
+
+        rpc_id: rpc_info.and_then(|rpc| rpc.id.as_ref().map(|id| id.to_string())),
+        rpc_method: rpc_info.and_then(|rpc| rpc.method.clone()),
+
+        user_id: ctx.as_ref().map(|c| c.user_id()),
+        user_role: ctx.as_ref().map(|c| c.role().as_ref().to_string()),
+
+        client_error_type: client_error.map(|e| e.as_ref().to_string()),
+
+        error_type,
+        error_data,
+    };
+
+    // Hand it to the background task (never waits).
+    let line = serde_json::to_string(&log_line).map_err(|_| Error::LogLineSerializeFail)?;
+    logger.send(line);
+
+    Ok(())
+}
+
+#[skip_serializing_none]
+#[derive(Serialize)]
+struct RequestLogLine {
+    req_id: String,    // X-Request-Id (uuid when generated)
+    timestamp: String, // iso8601 (rfc3339)
+
+    // -- User and context attributes.
+    user_id: Option<u64>,
+    user_role: Option<String>,
+
+    // -- http request attributes.
+    req_path: String,
+    req_method: String,
+
+    // -- rpc info.
+    rpc_id: Option<String>,
+    rpc_method: Option<String>,
+
+    // -- Errors attributes.
+    client_error_type: Option<String>,
+    error_type: Option<String>,
+    error_data: Option<Value>,
+}
+
+// region:    --- Request Logger
+enum LogMsg {
+    Line(String),
+    /// Write the pending lines, flush the sinks, and reply with the totals.
+    Flush(oneshot::Sender<LogTotals>),
+}
+
+/// Since the logger start.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LogTotals {
+    /// Lines written by at least one sink.
+    pub lines_written: u64,
+    /// Lines dropped because the channel was full.
+    pub lines_dropped: u64,
+    /// Failed sink writes and flushes.
+    pub sink_errors: u64,
+}
+
+/// Handle to the background log task (cheap to clone).
+#[derive(Clone)]
+pub struct RequestLogger {
+    tx: mpsc::Sender<LogMsg>,
+    /// Lines dropped because the channel was full (reported and reset by the task).
+    dropped: Arc<AtomicU64>,
+}
+
+impl RequestLogger {
+    /// Build the sinks and spawn the background task.
+    pub async fn start(sink_configs: &[LogSinkConfig]) -> Result<Self> {
+        let mut sinks = Vec::with_capacity(sink_configs.len());
+        for sink_config in sink_configs {
+            sinks.push(sink::new_sink(sink_config).await?);
+        }
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        tokio::spawn(run_log_task(rx, sinks, dropped.clone()));
+
+        Ok(Self { tx, dropped })
+    }
+
+    /// Writes the lines sent so far and flushes the sinks (e.g., on shutdown).
+    pub async fn flush(&self) -> Result<LogTotals> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        // Waits for room in the channel (after the pending lines).
+        self.tx
+            .send(LogMsg::Flush(reply_tx))
+            .await
+            .map_err(|_| Error::LogTaskGone)?;
+
+        reply_rx.await.map_err(|_| Error::LogTaskGone)
+    }
+
+    fn send(&self, line: String) {
+        if self.tx.try_send(LogMsg::Line(line)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+async fn run_log_task(
+    mut rx: mpsc::Receiver<LogMsg>,
+    mut sinks: Vec<Box<dyn LogSink>>,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut batch: Vec<String> = Vec::with_capacity(BATCH_MAX_LINES);
+    let mut totals = LogTotals::default();
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(LogMsg::Line(line)) => {
+                    batch.push(line);
+                    if batch.len() >= BATCH_MAX_LINES {
+                        write_batch(&mut sinks, &mut batch, &dropped, &mut totals).await;
+                    }
+                }
+                Some(LogMsg::Flush(reply_tx)) => {
+                    write_batch(&mut sinks, &mut batch, &dropped, &mut totals).await;
+                    flush_sinks(&mut sinks, &mut totals).await;
+                    let _ = reply_tx.send(totals);
+                }
+                // All the loggers are gone.
+                None => {
+                    write_batch(&mut sinks, &mut batch, &dropped, &mut totals).await;
+                    flush_sinks(&mut sinks, &mut totals).await;
+                    break;
+                }
+            },
+            _ = interval.tick() => {
+                write_batch(&mut sinks, &mut batch, &dropped, &mut totals).await;
+            }
+        }
+    }
+}
+
+/// Sink errors are traced, the batch is not retried.
+async fn write_batch(
+    sinks: &mut [Box<dyn LogSink>],
+    batch: &mut Vec<String>,
+    dropped: &AtomicU64,
+    totals: &mut LogTotals,
+) {
+    let dropped = dropped.swap(0, Ordering::Relaxed);
+    if dropped > 0 {
+        warn!("{:<12} - {dropped} log lines dropped (channel full)", "LOG");
+        totals.lines_dropped += dropped;
+    }
+
+    if batch.is_empty() {
+        return;
+    }
+
+    let mut written = false;
+    for sink in sinks.iter_mut() {
+        match sink.write(batch).await {
+            Ok(()) => written = true,
+            Err(ex) => {
+                error!("{:<12} - sink write fail - {ex:?}", "LOG");
+                totals.sink_errors += 1;
+            }
+        }
+    }
+    if written {
+        totals.lines_written += batch.len() as u64;
+    }
+    batch.clear();
+}
+
+async fn flush_sinks(sinks: &mut [Box<dyn LogSink>], totals: &mut LogTotals) {
+    for sink in sinks.iter_mut() {
+        if let Err(ex) = sink.flush().await {
+            error!("{:<12} - sink flush fail - {ex:?}", "LOG");
+            totals.sink_errors += 1;
+        }
+    }
+}
+// endregion: --- Request Logger
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_logger_flush() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("log-flush-test-{}", uuid::Uuid::new_v4()));
+        let sink_config = LogSinkConfig::File {
+            dir: dir.clone(),
+            max_bytes: 1024 * 1024,
+            max_age: Duration::from_secs(3600),
+        };
+        let logger = RequestLogger::start(&[sink_config]).await?;
+
+        logger.send(r#"{"n":0}"#.to_string());
+        logger.send(r#"{"n":1}"#.to_string());
+        // Written before the next flush interval.
+        let totals = logger.flush().await?;
+
+        assert_eq!(
+            totals,
+            LogTotals {
+                lines_written: 2,
+                ..Default::default()
+            }
+        );
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "{\"n\":0}\n{\"n\":1}\n"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_logger_sink_fail() -> Result<()> {
+        struct FailSink;
+
+        #[async_trait::async_trait]
+        impl LogSink for FailSink {
+            async fn write(&mut self, _lines: &[String]) -> Result<()> {
+                Err(Error::LogSinkIo("disk full".to_string()))
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        tokio::spawn(run_log_task(rx, vec![Box::new(FailSink)], dropped.clone()));
+        let logger = RequestLogger { tx, dropped };
+
+        logger.send(r#"{"n":0}"#.to_string());
+        let totals = logger.flush().await?;
+
+        // Not written by any sink.
+        assert_eq!(
+            totals,
+            LogTotals {
+                sink_errors: 1,
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
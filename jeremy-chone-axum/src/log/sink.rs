@@ -0,0 +1,334 @@
+//! Request log sinks
+//! - `StdoutSink` - one json line per request on stdout.
+//! - `FileSink`   - newline-delimited json files, rotated by size and age.
+//! - `HttpSink`   - POST of each batch (ndjson body) to a collector.
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::Bytes,
+    http::{header, Request, Uri},
+};
+use http_body_util::Full;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::{Error, Result};
+
+const HTTP_SINK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug)]
+pub enum LogSinkConfig {
+    Stdout,
+    File {
+        dir: PathBuf,
+        max_bytes: u64,
+        max_age: Duration,
+    },
+    /// `http://` only.
+    Http {
+        url: String,
+    },
+}
+
+#[async_trait]
+pub trait LogSink: Send {
+    /// Each line is a serialized json object (no trailing newline).
+    async fn write(&mut self, lines: &[String]) -> Result<()>;
+
+    async fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub async fn new_sink(config: &LogSinkConfig) -> Result<Box<dyn LogSink>> {
+    let sink: Box<dyn LogSink> = match config {
+        LogSinkConfig::Stdout => Box::new(StdoutSink),
+        LogSinkConfig::File {
+            dir,
+            max_bytes,
+            max_age,
+        } => Box::new(FileSink::new(dir.clone(), *max_bytes, *max_age).await?),
+        LogSinkConfig::Http { url } => Box::new(HttpSink::new(url)?),
+    };
+
+    Ok(sink)
+}
+
+// region:    --- Stdout Sink
+/// The bare lines on stdout (ndjson).
+pub struct StdoutSink;
+
+#[async_trait]
+impl LogSink for StdoutSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        for line in lines {
+            println!("{line}");
+        }
+        Ok(())
+    }
+}
+// endregion: --- Stdout Sink
+
+// region:    --- File Sink
+pub struct FileSink {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+    current: Option<CurrentFile>,
+}
+
+struct CurrentFile {
+    writer: BufWriter<File>,
+    bytes: u64,
+    opened_at: Instant,
+}
+
+impl FileSink {
+    pub async fn new(dir: PathBuf, max_bytes: u64, max_age: Duration) -> Result<Self> {
+        fs::create_dir_all(&dir).await.map_err(io_err)?;
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            max_age,
+            current: None,
+        })
+    }
+
+    /// A new file is started when the batch would make the current one go over
+    /// `max_bytes`, or when the current one is older than `max_age`.
+    fn needs_rotation(&self, batch_bytes: u64) -> bool {
+        match &self.current {
+            None => true,
+            Some(current) => {
+                (current.bytes > 0 && current.bytes + batch_bytes > self.max_bytes)
+                    || current.opened_at.elapsed() >= self.max_age
+            }
+        }
+    }
+
+    async fn rotate(&mut self) -> Result<()> {
+        if let Some(mut current) = self.current.take() {
+            current.writer.flush().await.map_err(io_err)?;
+        }
+
+        // File names sort by creation time, e.g., `requests-1792314428246.ndjson`
+        let mut millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|ex| Error::LogSinkIo(ex.to_string()))?
+            .as_millis();
+        let file = loop {
+            let path = self.dir.join(format!("requests-{millis}.ndjson"));
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .await
+            {
+                Ok(file) => break file,
+                Err(ex) if ex.kind() == std::io::ErrorKind::AlreadyExists => millis += 1,
+                Err(ex) => return Err(io_err(ex)),
+            }
+        };
+
+        self.current = Some(CurrentFile {
+            writer: BufWriter::new(file),
+            bytes: 0,
+            opened_at: Instant::now(),
+        });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LogSink for FileSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        let batch_bytes: u64 = lines.iter().map(|l| l.len() as u64 + 1).sum();
+        if self.needs_rotation(batch_bytes) {
+            self.rotate().await?;
+        }
+
+        if let Some(current) = self.current.as_mut() {
+            for line in lines {
+                current
+                    .writer
+                    .write_all(line.as_bytes())
+                    .await
+                    .map_err(io_err)?;
+                current.writer.write_all(b"\n").await.map_err(io_err)?;
+            }
+            current.writer.flush().await.map_err(io_err)?;
+            current.bytes += batch_bytes;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if let Some(current) = self.current.as_mut() {
+            current.writer.flush().await.map_err(io_err)?;
+            current.writer.get_mut().sync_all().await.map_err(io_err)?;
+        }
+        Ok(())
+    }
+}
+
+fn io_err(ex: std::io::Error) -> Error {
+    Error::LogSinkIo(ex.to_string())
+}
+// endregion: --- File Sink
+
+// region:    --- Http Sink
+pub struct HttpSink {
+    uri: Uri,
+    client: Client<HttpConnector, Full<Bytes>>,
+}
+
+impl HttpSink {
+    pub fn new(url: &str) -> Result<Self> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|_| Error::LogSinkHttp(format!("invalid url '{url}'")))?;
+        if uri.scheme_str() != Some("http") {
+            return Err(Error::LogSinkHttp(format!(
+                "only http:// supported '{url}'"
+            )));
+        }
+
+        let client = Client::builder(TokioExecutor::new()).build_http();
+
+        Ok(Self { uri, client })
+    }
+}
+
+#[async_trait]
+impl LogSink for HttpSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        let mut body = lines.join("\n");
+        body.push('\n');
+
+        let req = Request::post(self.uri.clone())
+            .header(header::CONTENT_TYPE, "application/x-ndjson")
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|ex| Error::LogSinkHttp(ex.to_string()))?;
+
+        let res = tokio::time::timeout(HTTP_SINK_TIMEOUT, self.client.request(req))
+            .await
+            .map_err(|_| Error::LogSinkHttp("timeout".to_string()))?
+            .map_err(|ex| Error::LogSinkHttp(ex.to_string()))?;
+
+        if !res.status().is_success() {
+            return Err(Error::LogSinkHttp(format!(
+                "collector status {}",
+                res.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+// endregion: --- Http Sink
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::State, routing::post, Router};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!(r#"{{"n":{i}}}"#)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_rotate_by_size() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("log-sink-test-{}", uuid::Uuid::new_v4()));
+        // Each line is 8 bytes (with the newline), 3 lines per file max.
+        let mut sink = FileSink::new(dir.clone(), 24, Duration::from_secs(3600)).await?;
+
+        sink.write(&lines(0..3)).await?;
+        sink.write(&lines(3..5)).await?;
+        sink.write(&lines(5..6)).await?;
+        sink.flush().await?;
+
+        let mut paths: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        paths.sort();
+        let contents: Vec<String> = paths
+            .iter()
+            .map(|p| std::fs::read_to_string(p).unwrap())
+            .collect();
+
+        assert_eq!(contents.len(), 2);
+        assert_eq!(contents[0], "{\"n\":0}\n{\"n\":1}\n{\"n\":2}\n");
+        assert_eq!(contents[1], "{\"n\":3}\n{\"n\":4}\n{\"n\":5}\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http_sink_post_ndjson() -> Result<()> {
+        // -- Local stand-in for the collector.
+        let received: Arc<Mutex<Vec<String>>> = Arc::default();
+        let collector = Router::new()
+            .route(
+                "/logs",
+                post(
+                    |State(received): State<Arc<Mutex<Vec<String>>>>, body: String| async move {
+                        received.lock().unwrap().push(body);
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, collector).await.unwrap() });
+
+        let mut sink = HttpSink::new(&format!("http://{addr}/logs"))?;
+        sink.write(&lines(0..2)).await?;
+        sink.write(&lines(2..3)).await?;
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(
+            received,
+            vec![
+                "{\"n\":0}\n{\"n\":1}\n".to_string(),
+                "{\"n\":2}\n".to_string()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http_sink_collector_error() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, Router::new()).await.unwrap() });
+
+        let mut sink = HttpSink::new(&format!("http://{addr}/missing"))?;
+        let res = sink.write(&lines(0..1)).await;
+
+        assert!(matches!(res, Err(Error::LogSinkHttp(_))));
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -1,31 +1,16 @@
-use std::net::SocketAddr;
-
-use crate::{log::log_request, model::ModelController};
-
-pub use self::error::{Error, Result};
+use std::{
+    future::{Future, IntoFuture},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 
-use axum::{
-    extract::{Path, Query},
-    http::{Method, Uri},
-    middleware,
-    response::{Html, IntoResponse, Response},
-    routing::{get, get_service},
-    Json, Router,
+use jeremy_chone_axum::{
+    _dev_utils, config::Config, log::RequestLogger, model, model::ModelController, routes_all,
+    trace, web::mw_metrics::Metrics, Error, Result,
 };
-use ctx::Ctx;
-use serde::Deserialize;
-use serde_json::json;
-use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_cookies::CookieManagerLayer;
-use tower_http::services::ServeDir;
-use uuid::Uuid;
-
-mod ctx;
-mod error;
-mod log;
-mod model;
-mod web;
+use tokio::{net::TcpListener, signal, sync::watch};
+use tracing::{error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -37,112 +22,145 @@ async fn main() -> Result<()> {
     //     )
     //     .route("/hello2/:name", get(handler_hello2));
 
+    // Load config (fail fast on missing/invalid file or env values).
+    let config = Arc::new(Config::load()?);
+
+    // Tracing (`RUST_LOG` or the config filter).
+    trace::init_tracing(&config)?;
+
     // Initialize ModelController
-    let mc = ModelController::new().await.unwrap();
-
-    // 这个中间件仅作用于 routes_apis
-    let routes_apis = web::routes_tickets::routes(mc.clone())
-        .route_layer(middleware::from_fn(web::mw_auth::mw_require_auth));
-
-    // merge routes
-    let routes_all = Router::new()
-        .merge(routes_hello())
-        .merge(web::routes_login::routes())
-        .nest("/api", routes_apis)
-        .layer(
-            // ServiceBuilder 符合直觉, 自上而下, 依次添加中间件, layer是自下而上
-            ServiceBuilder::new()
-                .layer(CookieManagerLayer::new())
-                .layer(middleware::from_fn_with_state(
-                    mc.clone(),
-                    web::mw_auth::mw_ctx_resolver,
-                ))
-                .layer(middleware::map_response(main_response_mapper)),
-        )
-        .fallback_service(routes_static());
+    let mc = ModelController::new(config.db_url.as_deref()).await?;
 
-    // region:    --- Start Server
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3089));
-    let listener = TcpListener::bind(addr).await.unwrap();
-    println!("Listening on {}", addr);
-    axum::serve(listener, routes_all.into_make_service())
-        .await
-        .unwrap();
-    // endregion: --- Start Server
-    Ok(())
-}
+    // Initialize the request logger (background task writing to the sinks).
+    let logger = RequestLogger::start(&config.log_sinks).await?;
 
-/// consume Response, return different Response(modify, or not)
-async fn main_response_mapper(
-    ctx: Option<Ctx>,
-    uri: Uri,
-    req_method: Method,
-    res: Response,
-) -> Response {
-    println!("->> {:12} - main-response_mapper", "RES_MAPPER");
-    let uuid = Uuid::new_v4();
-    // -- Get the eventual response error.
-    let service_error = res.extensions().get::<Error>();
-
-    // convert the error into a client error.
-    let client_status_error = service_error.map(|se| se.client_status_and_error());
-
-    // -- If client error, build the new response
-    let error_response = client_status_error
-        .as_ref()
-        .map(|(status_code, client_error)| {
-            let client_error_body = json!({
-                    "error" : {
-                        "type": client_error.as_ref(),
-                        "req_uuid": uuid.to_string(),
-                    }
-                }
-            );
-            println!("    ->> client_error_body: {client_error_body}");
+    // Purge the tickets past their trash retention period.
+    let trash_purge = model::spawn_trash_purge(
+        mc.clone(),
+        Duration::from_secs(config.ticket_trash_retention_sec),
+        Duration::from_secs(config.ticket_trash_purge_interval_sec),
+    );
 
-            // Build the new response from the client_error body.
-            (*status_code, Json(client_error_body)).into_response()
-        });
+    // Request metrics, recorded by the `mw_metrics` layer, exposed at /metrics.
+    let metrics = Metrics::default();
 
-    // Build and log the server log line.
-    // println!("    ->> server log line - {uuid} - Error: {service_error:?}");
-    let client_error = client_status_error.unzip().1;
-    let _ = log_request(uuid, req_method, uri, ctx, service_error, client_error).await;
+    // FOR DEV ONLY - seed the demo users (when `dev_seed_pwd` is set).
+    _dev_utils::init_dev(&mc, &config).await?;
 
-    println!();
-    error_response.unwrap_or(res)
-}
+    let addr = config.bind_addr;
+    let drain_timeout = Duration::from_secs(config.shutdown_drain_timeout_sec);
+    let routes_all = routes_all(config, mc.clone(), logger.clone(), metrics.clone());
 
-fn routes_static() -> Router {
-    // http://localhost:3089/index.html -> ./index.html
-    Router::new().nest_service("/", get_service(ServeDir::new("./")))
-}
+    // region:    --- Start Server
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|ex| Error::ServerBindFail {
+            addr: addr.to_string(),
+            cause: ex.to_string(),
+        })?;
+    info!("Listening on {addr}");
+
+    // Installed before serving, so a failure stops the startup.
+    let shutdown_signal = shutdown_signal()?;
+
+    // Set once the shutdown signal is received (starts the drain timeout).
+    let (draining_tx, mut draining_rx) = watch::channel(false);
+    let mc_for_signal = mc.clone();
+
+    // Connect info for the per client ip rate limits.
+    let server = axum::serve(
+        listener,
+        routes_all.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    // Stops accepting, then waits for the open connections.
+    .with_graceful_shutdown(async move {
+        shutdown_signal.await;
+        info!("{:<12} - signal received, draining", "SHUTDOWN");
+        // The event streams would hold their connection open.
+        mc_for_signal.end_subscriptions();
+        draining_tx.send_replace(true);
+    });
+
+    let drain_deadline = async {
+        let _ = draining_rx.wait_for(|draining| *draining).await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+
+    let drained = tokio::select! {
+        res = server.into_future() => {
+            if let Err(ex) = res {
+                error!("{:<12} - server error: {ex:?}", "SHUTDOWN");
+            }
+            true
+        }
+        _ = drain_deadline => {
+            warn!(
+                "{:<12} - drain timeout - {} requests abandoned",
+                "SHUTDOWN",
+                metrics.in_flight()
+            );
+            false
+        }
+    };
+    // endregion: --- Start Server
 
-// region:    --- Routes Hello
-#[derive(Debug, Deserialize)]
-struct HelloParams {
-    name: Option<String>,
-}
-// 模式匹配, 从 Query 中提取参数
-async fn handler_hello(Query(params): Query<HelloParams>) -> impl IntoResponse {
-    println!("->> {:12} - handler_hello - {params:?}", "HANDLER");
-    let name = params.name.as_deref().unwrap_or("World!");
-    Html(format!("Hello <strong>{name}</strong>"))
-}
+    // region:    --- Shutdown
+    trash_purge.abort();
+
+    // After the drain, so the lines of the served requests are in.
+    let log_totals = logger.flush().await;
+    if let Err(ex) = &log_totals {
+        error!("{:<12} - log flush fail - {ex:?}", "SHUTDOWN");
+    }
+
+    // Only the sql store (on a db file or server) is persisted, not the in-memory ones.
+    let store_persisted = match mc.close().await {
+        Ok(persisted) => persisted,
+        Err(ex) => {
+            error!("{:<12} - store close fail - {ex:?}", "SHUTDOWN");
+            false
+        }
+    };
+
+    let log_totals = log_totals.unwrap_or_default();
+    info!(
+        drained,
+        log_lines_written = log_totals.lines_written,
+        log_lines_dropped = log_totals.lines_dropped,
+        log_sink_errors = log_totals.sink_errors,
+        store_persisted,
+        "{:<12} - complete",
+        "SHUTDOWN"
+    );
+    // endregion: --- Shutdown
 
-async fn handler_hello2(Path(name): Path<String>) -> impl IntoResponse {
-    println!("->> {:12} - handler_hello2 - {name:?}", "HANDLER");
+    Ok(())
+}
 
-    Html(format!("Hello <strong>{name}</strong>"))
+/// Ctrl+C (SIGINT) or SIGTERM (e.g., container stop).
+#[cfg(unix)]
+fn shutdown_signal() -> Result<impl Future<Output = ()>> {
+    use signal::unix::{signal, SignalKind};
+
+    let signal_fail = |ex: std::io::Error| Error::ServerSignalFail(ex.to_string());
+    let mut interrupt = signal(SignalKind::interrupt()).map_err(signal_fail)?;
+    let mut terminate = signal(SignalKind::terminate()).map_err(signal_fail)?;
+
+    Ok(async move {
+        tokio::select! {
+            _ = interrupt.recv() => (),
+            _ = terminate.recv() => (),
+        }
+    })
 }
 
-fn routes_hello() -> Router {
-    Router::new()
-        .route(
-            "/hello",
-            // get(|| async { Html("Hello <strong>World!!!</strong>") }),
-            get(handler_hello),
-        )
-        .route("/hello2/:name", get(handler_hello2))
+/// Ctrl+C.
+#[cfg(windows)]
+fn shutdown_signal() -> Result<impl Future<Output = ()>> {
+    let mut ctrl_c =
+        signal::windows::ctrl_c().map_err(|ex| Error::ServerSignalFail(ex.to_string()))?;
+
+    Ok(async move {
+        ctrl_c.recv().await;
+    })
 }
-// endregion: --- Routes Hello
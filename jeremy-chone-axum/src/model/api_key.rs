@@ -0,0 +1,157 @@
+//! API Key Backend Model Controller
+//! (keys for machine clients, scoped to a user and a permission subset)
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypt::{api_key::ApiKeyClear, token::now_unix_sec},
+    ctx::Ctx,
+    Error, Result,
+};
+
+use super::{
+    user::{Permission, UserBmc, UserForAuth},
+    ModelController,
+};
+
+// region:    --- Api Key Types
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiKey {
+    /// Public key id (the `key-id` part of the clear key).
+    pub id: String,
+    pub user_id: u64,
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    /// Creation time (unix sec).
+    pub ctime: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ApiKeyForCreate {
+    pub name: String,
+    /// Must be a subset of the creator permissions (without `ApiKeyManage`).
+    pub permissions: Vec<Permission>,
+}
+
+/// Returned once, on creation, the clear key is not stored.
+#[derive(Serialize)]
+pub struct ApiKeyCreated {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// Stored api key record, never sent to the client.
+#[derive(Clone)]
+pub struct ApiKeyForAuth {
+    pub api_key: ApiKey,
+    pub secret_hash: String,
+}
+// endregion: --- Api Key Types
+
+// region:    --- Api Key Bmc
+pub struct ApiKeyBmc;
+
+impl ApiKeyBmc {
+    /// Creates a key for the ctx user, with the given permissions (which the ctx must have).
+    pub async fn create(
+        mc: &ModelController,
+        ctx: &Ctx,
+        api_key_fc: ApiKeyForCreate,
+    ) -> Result<ApiKeyCreated> {
+        ctx.require(Permission::ApiKeyManage)?;
+        let ApiKeyForCreate { name, permissions } = api_key_fc;
+
+        if name.trim().is_empty()
+            || permissions.is_empty()
+            || permissions.contains(&Permission::ApiKeyManage)
+        {
+            return Err(Error::ApiKeyInvalidParams);
+        }
+        let mut key_permissions: Vec<Permission> = Vec::with_capacity(permissions.len());
+        for permission in permissions {
+            ctx.require(permission)?;
+            if !key_permissions.contains(&permission) {
+                key_permissions.push(permission);
+            }
+        }
+
+        let key_clear = ApiKeyClear::generate();
+        let api_key = ApiKey {
+            id: key_clear.key_id.clone(),
+            user_id: ctx.user_id(),
+            name,
+            permissions: key_permissions,
+            ctime: now_unix_sec(),
+        };
+        mc.store
+            .api_key_insert(ApiKeyForAuth {
+                api_key: api_key.clone(),
+                secret_hash: key_clear.secret_hash(),
+            })
+            .await?;
+
+        Ok(ApiKeyCreated {
+            api_key,
+            key: key_clear.to_string(),
+        })
+    }
+
+    /// The keys of the ctx user.
+    pub async fn list(mc: &ModelController, ctx: &Ctx) -> Result<Vec<ApiKey>> {
+        ctx.require(Permission::ApiKeyManage)?;
+        let api_keys = mc.store.api_key_list(ctx.user_id()).await?;
+
+        Ok(api_keys.into_iter().map(|k| k.api_key).collect())
+    }
+
+    /// Revocable by the key owner, and by the users with `UserManage`.
+    pub async fn revoke(mc: &ModelController, ctx: &Ctx, id: &str) -> Result<ApiKey> {
+        ctx.require(Permission::ApiKeyManage)?;
+        let api_key = mc
+            .store
+            .api_key_get(id)
+            .await?
+            .ok_or_else(|| Error::ApiKeyNotFound { id: id.to_string() })?
+            .api_key;
+
+        if api_key.user_id != ctx.user_id() && !ctx.has_permission(Permission::UserManage) {
+            return Err(Error::AccessDenied);
+        }
+
+        mc.store.api_key_delete(id).await?;
+
+        Ok(api_key)
+    }
+
+    /// Returns the key user and the key effective permissions (the key
+    /// permissions still granted by the current user role, never `ApiKeyManage`).
+    pub async fn auth(
+        mc: &ModelController,
+        key_clear: &ApiKeyClear,
+    ) -> Result<(UserForAuth, Vec<Permission>)> {
+        let ApiKeyForAuth {
+            api_key,
+            secret_hash,
+        } = mc
+            .store
+            .api_key_get(&key_clear.key_id)
+            .await?
+            .ok_or(Error::AuthFailApiKeyInvalid)?;
+        key_clear.validate(&secret_hash)?;
+
+        let user = UserBmc::get(mc, api_key.user_id)
+            .await
+            .map_err(|_| Error::AuthFailUserNotFound)?;
+        let role_permissions = user.role.permissions();
+        let permissions = api_key
+            .permissions
+            .iter()
+            .copied()
+            .filter(|p| *p != Permission::ApiKeyManage && role_permissions.contains(p))
+            .collect();
+
+        Ok((user, permissions))
+    }
+}
+// endregion: --- Api Key Bmc
@@ -0,0 +1,231 @@
+//! Ticket Audit Trail Backend Model Controller
+//! (append-only, one event per ticket create/update/transition/delete/restore/purge)
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tracing::error;
+
+use crate::{crypt::token::now_unix_sec, ctx::Ctx, Result};
+
+use super::{user::Permission, ListOptions, ModelController, Page, Ticket};
+
+// region:    --- Audit Types
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum_macros::AsRefStr,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Transition,
+    /// Moved to the trash.
+    Delete,
+    Restore,
+    /// Permanently removed by the trash purge task (`SYSTEM_ACTOR_ID`).
+    Purge,
+}
+
+/// The `actor_id` of the events not done by a user (0 is not a valid user id).
+pub const SYSTEM_ACTOR_ID: u64 = 0;
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct AuditEvent {
+    pub id: u64,
+    pub ticket_id: u64,
+    /// The user_id who did the change.
+    pub actor_id: u64,
+    pub action: AuditAction,
+    /// Changed ticket fields, `{"field": {"before": .., "after": ..}}`
+    /// (`before` is null on create, `after` is null on purge).
+    pub diff: Value,
+    /// Event time (unix sec).
+    pub ctime: u64,
+}
+
+pub struct AuditEventForCreate {
+    pub ticket_id: u64,
+    pub actor_id: u64,
+    pub action: AuditAction,
+    pub diff: Value,
+    pub ctime: u64,
+}
+
+/// All set filters must match.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuditFilter {
+    pub ticket_id: Option<u64>,
+    pub actor_id: Option<u64>,
+}
+
+// endregion: --- Audit Types
+
+// region:    --- Audit Bmc
+pub struct AuditBmc;
+
+impl AuditBmc {
+    /// Appends the event for a ticket change (no-op when nothing changed).
+    ///
+    /// NOTE: The change is already committed, so a failure is logged, not returned
+    ///       (the caller still gets its result and publishes its event).
+    pub(super) async fn record(
+        mc: &ModelController,
+        ctx: &Ctx,
+        action: AuditAction,
+        before: Option<&Ticket>,
+        after: Option<&Ticket>,
+    ) {
+        Self::record_as(mc, ctx.user_id(), action, before, after).await
+    }
+
+    /// Like `record`, for the changes done by the service itself.
+    pub(super) async fn record_system(
+        mc: &ModelController,
+        action: AuditAction,
+        before: Option<&Ticket>,
+        after: Option<&Ticket>,
+    ) {
+        Self::record_as(mc, SYSTEM_ACTOR_ID, action, before, after).await
+    }
+
+    async fn record_as(
+        mc: &ModelController,
+        actor_id: u64,
+        action: AuditAction,
+        before: Option<&Ticket>,
+        after: Option<&Ticket>,
+    ) {
+        if let Err(ex) = Self::insert(mc, actor_id, action, before, after).await {
+            let ticket_id = after.or(before).map(|t| t.id);
+            error!(error = ?ex, ?ticket_id, ?action, "{:<12} - record - fail", "AUDIT");
+        }
+    }
+
+    async fn insert(
+        mc: &ModelController,
+        actor_id: u64,
+        action: AuditAction,
+        before: Option<&Ticket>,
+        after: Option<&Ticket>,
+    ) -> Result<()> {
+        let Some(ticket_id) = after.or(before).map(|t| t.id) else {
+            return Ok(());
+        };
+        let diff = ticket_diff(before, after);
+        if diff.is_empty() {
+            return Ok(());
+        }
+
+        mc.store
+            .audit_insert(AuditEventForCreate {
+                ticket_id,
+                actor_id,
+                action,
+                diff: Value::Object(diff),
+                ctime: now_unix_sec(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// History of a ticket readable by the ctx user, newest first.
+    pub async fn list_for_ticket(
+        mc: &ModelController,
+        ctx: &Ctx,
+        ticket_id: u64,
+        list_options: ListOptions,
+    ) -> Result<Page<AuditEvent>> {
+        mc.ticket_for_read(ctx, ticket_id).await?;
+
+        let filter = AuditFilter {
+            ticket_id: Some(ticket_id),
+            actor_id: None,
+        };
+        let (items, total) = mc.store.audit_list(&filter, &list_options).await?;
+
+        Ok(Page::new(items, total, &list_options))
+    }
+
+    /// Global feed (all tickets, including the deleted ones), requires `AuditRead`.
+    pub async fn list(
+        mc: &ModelController,
+        ctx: &Ctx,
+        filter: AuditFilter,
+        list_options: ListOptions,
+    ) -> Result<Page<AuditEvent>> {
+        ctx.require(Permission::AuditRead)?;
+
+        let (items, total) = mc.store.audit_list(&filter, &list_options).await?;
+
+        Ok(Page::new(items, total, &list_options))
+    }
+}
+
+/// Top-level ticket fields that differ.
+fn ticket_diff(before: Option<&Ticket>, after: Option<&Ticket>) -> Map<String, Value> {
+    let to_map = |ticket: Option<&Ticket>| match serde_json::to_value(ticket) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let (before, after) = (to_map(before), to_map(after));
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter(|k| before.get(*k) != after.get(*k))
+        .map(|k| {
+            let change = json!({
+                "before": before.get(k).cloned().unwrap_or(Value::Null),
+                "after": after.get(k).cloned().unwrap_or(Value::Null),
+            });
+            (k.clone(), change)
+        })
+        .collect()
+}
+// endregion: --- Audit Bmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TicketStatus;
+
+    #[test]
+    fn test_ticket_diff() {
+        let before = Ticket {
+            id: 1,
+            cid: 2,
+            title: "Old".to_string(),
+            status: TicketStatus::Open,
+            deleted_at: None,
+        };
+        let after = Ticket {
+            title: "New".to_string(),
+            ..before.clone()
+        };
+
+        let diff = ticket_diff(Some(&before), Some(&after));
+        assert_eq!(
+            Value::Object(diff),
+            json!({"title": {"before": "Old", "after": "New"}})
+        );
+
+        let diff = ticket_diff(None, Some(&before));
+        assert_eq!(diff["status"], json!({"before": null, "after": "open"}));
+        assert_eq!(diff.len(), 4);
+
+        assert!(ticket_diff(Some(&before), Some(&before)).is_empty());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,125 @@
+//! Ticket Comment Backend Model Controller
+//! (comments are removed with their ticket, see `Store::ticket_purge`)
+
+use serde::{Deserialize, Serialize};
+
+use crate::{crypt::token::now_unix_sec, ctx::Ctx, Error, Result};
+
+use super::{user::Permission, ModelController};
+
+// region:    --- Comment Types
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct Comment {
+    pub id: u64,
+    pub ticket_id: u64,
+    pub cid: u64, // author user_id
+    pub body: String,
+    /// Creation and last modification time (unix sec).
+    pub ctime: u64,
+    pub mtime: u64,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CommentForCreate {
+    pub body: String,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CommentForUpdate {
+    pub body: String,
+}
+// endregion: --- Comment Types
+
+// region:    --- Comment Bmc
+pub struct CommentBmc;
+
+impl CommentBmc {
+    /// Anyone who can read the ticket can comment, given they have `TicketUpdate`.
+    pub async fn create(
+        mc: &ModelController,
+        ctx: &Ctx,
+        ticket_id: u64,
+        comment_fc: CommentForCreate,
+    ) -> Result<Comment> {
+        ctx.require(Permission::TicketUpdate)?;
+        mc.ticket_for_read(ctx, ticket_id).await?;
+        let body = validate_body(comment_fc.body)?;
+
+        mc.store
+            .comment_insert(ticket_id, ctx.user_id(), body, now_unix_sec())
+            .await
+    }
+
+    /// Oldest first.
+    pub async fn list(mc: &ModelController, ctx: &Ctx, ticket_id: u64) -> Result<Vec<Comment>> {
+        mc.ticket_for_read(ctx, ticket_id).await?;
+
+        mc.store.comment_list(ticket_id).await
+    }
+
+    /// Editable by the author only.
+    pub async fn update(
+        mc: &ModelController,
+        ctx: &Ctx,
+        ticket_id: u64,
+        id: u64,
+        comment_fu: CommentForUpdate,
+    ) -> Result<Comment> {
+        ctx.require(Permission::TicketUpdate)?;
+        let comment = Self::comment_for_read(mc, ctx, ticket_id, id).await?;
+        if comment.cid != ctx.user_id() {
+            return Err(Error::AccessDenied);
+        }
+        let body = validate_body(comment_fu.body)?;
+
+        mc.store
+            .comment_update(id, body, now_unix_sec())
+            .await?
+            .ok_or(Error::CommentNotFound { id })
+    }
+
+    /// Deletable by the author, the ticket creator, and the ticket admins.
+    pub async fn delete(
+        mc: &ModelController,
+        ctx: &Ctx,
+        ticket_id: u64,
+        id: u64,
+    ) -> Result<Comment> {
+        ctx.require(Permission::TicketUpdate)?;
+        let comment = Self::comment_for_read(mc, ctx, ticket_id, id).await?;
+        if comment.cid != ctx.user_id() {
+            mc.ticket_for_write(ctx, ticket_id, Permission::TicketUpdate)
+                .await?;
+        }
+
+        mc.store
+            .comment_delete(id)
+            .await?
+            .ok_or(Error::CommentNotFound { id })
+    }
+
+    /// The comment `id` of the (readable) ticket `ticket_id`.
+    async fn comment_for_read(
+        mc: &ModelController,
+        ctx: &Ctx,
+        ticket_id: u64,
+        id: u64,
+    ) -> Result<Comment> {
+        mc.ticket_for_read(ctx, ticket_id).await?;
+
+        mc.store
+            .comment_get(id)
+            .await?
+            .filter(|c| c.ticket_id == ticket_id)
+            .ok_or(Error::CommentNotFound { id })
+    }
+}
+
+fn validate_body(body: String) -> Result<String> {
+    if body.trim().is_empty() {
+        Err(Error::CommentInvalidParams)
+    } else {
+        Ok(body)
+    }
+}
+// endregion: --- Comment Bmc
@@ -0,0 +1,184 @@
+//! Live Ticket Events
+//! (broadcast of the `ModelController` ticket mutations, filtered per subscriber)
+//!
+//! NOTE: In-memory, per server instance. A subscriber too slow to keep up
+//!       skips the events it missed (see `TICKET_EVENTS_CAPACITY`).
+
+use serde::Serialize;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    watch,
+};
+use tracing::warn;
+
+use crate::{ctx::Ctx, Result};
+
+use super::{user::Permission, ModelController, Ticket};
+
+/// Events buffered per subscriber before it lags.
+pub(super) const TICKET_EVENTS_CAPACITY: usize = 256;
+
+// region:    --- Ticket Event Types
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, strum_macros::AsRefStr, utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TicketEventKind {
+    Created,
+    /// Title or status change.
+    Updated,
+    /// Moved to the trash.
+    Deleted,
+    Restored,
+}
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct TicketEvent {
+    pub kind: TicketEventKind,
+    /// The ticket after the change.
+    pub ticket: Ticket,
+}
+// endregion: --- Ticket Event Types
+
+// region:    --- Ticket Event Bmc
+pub struct TicketEventBmc;
+
+impl TicketEventBmc {
+    /// Sends the event to the current subscribers (dropped when none).
+    pub(super) fn publish(mc: &ModelController, kind: TicketEventKind, ticket: &Ticket) {
+        let _ = mc.events.send(TicketEvent {
+            kind,
+            ticket: ticket.clone(),
+        });
+    }
+
+    /// The events of the tickets readable by the ctx user, from now on.
+    ///
+    /// NOTE: The ctx is the one at subscribe time (role changes apply on the next subscribe).
+    pub fn subscribe(mc: &ModelController, ctx: Ctx) -> Result<TicketEventSubscription> {
+        ctx.require(Permission::TicketRead)?;
+
+        Ok(TicketEventSubscription {
+            mc: mc.clone(),
+            ctx,
+            rx: mc.events.subscribe(),
+            closing: mc.closing.subscribe(),
+        })
+    }
+}
+
+pub struct TicketEventSubscription {
+    mc: ModelController,
+    ctx: Ctx,
+    rx: broadcast::Receiver<TicketEvent>,
+    closing: watch::Receiver<bool>,
+}
+
+impl TicketEventSubscription {
+    /// Waits for the next visible event, `None` when the channel is closed (or on shutdown).
+    /// NOTE: Not cancel safe (the event is taken before the visibility check),
+    ///       in a `select!` use `recv` and `is_visible` instead.
+    pub async fn next(&mut self) -> Option<TicketEvent> {
+        loop {
+            let event = self.recv().await?;
+            if self.is_visible(&event).await {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Waits for the next event, visible or not, `None` when the channel is closed (or on shutdown).
+    /// Cancel safe (no event is lost when dropped before completion).
+    pub async fn recv(&mut self) -> Option<TicketEvent> {
+        loop {
+            let recv = tokio::select! {
+                recv = self.rx.recv() => recv,
+                _ = self.closing.wait_for(|closing| *closing) => return None,
+            };
+
+            match recv {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    // Outlives the request span (streaming response).
+                    warn!(
+                        req_id = %self.ctx.req_id(),
+                        "{:<12} - subscription lagged - {skipped} skipped",
+                        "EVENTS"
+                    );
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Whether the subscriber can see the event ticket.
+    pub async fn is_visible(&self, event: &TicketEvent) -> bool {
+        // On store error, better skip the event than leak it.
+        self.is_ticket_visible(&event.ticket).await.unwrap_or(false)
+    }
+
+    /// Same rule as the ticket read (ticket admins, the creator, and the users it is shared with).
+    async fn is_ticket_visible(&self, ticket: &Ticket) -> Result<bool> {
+        if self.ctx.has_permission(Permission::TicketAdmin) || ticket.cid == self.ctx.user_id() {
+            return Ok(true);
+        }
+
+        let shared_with = self.mc.store.ticket_share_list(ticket.id).await?;
+        Ok(shared_with.contains(&self.ctx.user_id()))
+    }
+}
+// endregion: --- Ticket Event Bmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{user::Role, TicketForCreate, TicketForUpdate},
+        web::mw_req_id::ReqId,
+    };
+
+    #[tokio::test]
+    async fn test_subscription_visibility() -> Result<()> {
+        let mc = ModelController::new(None).await?;
+        let ctx = |user_id| {
+            Ctx::new(
+                ReqId::new(),
+                user_id,
+                Role::Member,
+                Role::Member.permissions().to_vec(),
+            )
+        };
+        let ticket_fc = |title: &str| TicketForCreate {
+            title: title.to_string(),
+        };
+
+        let mut subscription = TicketEventBmc::subscribe(&mc, ctx(2))?;
+
+        // Not visible to user 2 (created by user 1, not shared).
+        mc.create_ticket(ctx(1), ticket_fc("private")).await?;
+        // Visible to user 2 once shared.
+        let shared = mc.create_ticket(ctx(1), ticket_fc("shared")).await?;
+        mc.store.ticket_share_add(shared.id, 2).await?;
+        let ticket_fu = TicketForUpdate {
+            title: Some("shared, renamed".to_string()),
+        };
+        mc.update_ticket(ctx(1), shared.id, ticket_fu).await?;
+
+        // (visibility is checked on receive, so the share already applies)
+        let event = subscription.next().await.unwrap();
+        assert_eq!(event.kind, TicketEventKind::Created);
+        assert_eq!(event.ticket.id, shared.id);
+        let event = subscription.next().await.unwrap();
+        assert_eq!(event.kind, TicketEventKind::Updated);
+        assert_eq!(event.ticket.title, "shared, renamed");
+
+        // Shutdown ends the subscription.
+        mc.end_subscriptions();
+        assert!(subscription.next().await.is_none());
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
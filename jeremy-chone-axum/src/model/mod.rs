@@ -0,0 +1,616 @@
+//! Simplistic Model Layer
+//! (with pluggable store layer, see `store`)
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+use tracing::{error, info};
+
+use crate::{crypt::token::now_unix_sec, ctx::Ctx, Error, Result};
+
+use self::{
+    audit::{AuditAction, AuditBmc},
+    event::{TicketEvent, TicketEventBmc, TicketEventKind},
+    store::Store,
+    user::Permission,
+};
+
+pub mod api_key;
+pub mod audit;
+pub mod comment;
+pub mod event;
+pub mod session;
+pub mod store;
+pub mod user;
+
+// region:    --- Ticket Types
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct Ticket {
+    pub id: u64,
+    pub cid: u64, // creator user_id
+    pub title: String,
+    pub status: TicketStatus,
+    /// Set when in the trash (unix sec), purged after the retention period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<u64>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TicketForCreate {
+    pub title: String,
+}
+
+/// Partial update, `None` fields are left unchanged.
+/// (the status only changes through `transition_ticket`)
+#[derive(Default, Deserialize, utoipa::ToSchema)]
+pub struct TicketForUpdate {
+    pub title: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TicketForTransition {
+    pub status: TicketStatus,
+}
+// endregion: --- Ticket Types
+
+// region:    --- Ticket Status
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum_macros::AsRefStr,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum TicketStatus {
+    #[default]
+    Open,
+    InProgress,
+    Blocked,
+    Resolved,
+    Closed,
+}
+
+impl TicketStatus {
+    /// The workflow transition table.
+    pub fn can_transition_to(self, to: TicketStatus) -> bool {
+        use TicketStatus::*;
+
+        matches!(
+            (self, to),
+            (Open, InProgress | Blocked | Resolved | Closed)
+                | (InProgress, Open | Blocked | Resolved)
+                | (Blocked, Open | InProgress)
+                | (Resolved, Open | Closed)
+                | (Closed, Open)
+        )
+    }
+}
+
+impl FromStr for TicketStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "open" => Ok(Self::Open),
+            "in-progress" => Ok(Self::InProgress),
+            "blocked" => Ok(Self::Blocked),
+            "resolved" => Ok(Self::Resolved),
+            "closed" => Ok(Self::Closed),
+            _ => Err(Error::TicketStatusInvalid(s.to_string())),
+        }
+    }
+}
+// endregion: --- Ticket Status
+
+// region:    --- Ticket List Types
+/// All set filters must match.
+#[derive(Clone, Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TicketFilter {
+    /// Creator user_id.
+    pub cid: Option<u64>,
+    /// Case-insensitive title substring.
+    pub title: Option<String>,
+    pub status: Option<TicketStatus>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+pub enum TicketSort {
+    #[default]
+    #[serde(rename = "id")]
+    IdAsc,
+    #[serde(rename = "-id")]
+    IdDesc,
+    #[serde(rename = "title")]
+    TitleAsc,
+    #[serde(rename = "-title")]
+    TitleDesc,
+}
+
+/// Offset based pagination (`next_offset` of the page is the cursor for the next one).
+#[derive(Clone, Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListOptions {
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+    pub sort: Option<TicketSort>,
+}
+
+impl ListOptions {
+    pub const LIMIT_DEFAULT: u64 = 50;
+    pub const LIMIT_MAX: u64 = 500;
+    /// The SQL `OFFSET` is a signed 64 bits integer.
+    pub const OFFSET_MAX: u64 = i64::MAX as u64;
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+            .unwrap_or(Self::LIMIT_DEFAULT)
+            .clamp(1, Self::LIMIT_MAX)
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset.unwrap_or(0).min(Self::OFFSET_MAX)
+    }
+}
+
+/// One page of a list (tickets, audit events).
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items matching the filter (all pages).
+    pub total: u64,
+    /// Offset of the next page, `None` on the last page.
+    pub next_offset: Option<u64>,
+}
+
+impl<T> Page<T> {
+    fn new(items: Vec<T>, total: u64, list_options: &ListOptions) -> Self {
+        let end = list_options.offset() + items.len() as u64;
+        Self {
+            items,
+            total,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+// endregion: --- Ticket List Types
+
+// region:    --- Model Controller
+#[derive(Clone)]
+pub struct ModelController {
+    store: Arc<dyn Store>,
+    /// Ticket mutations, for the live subscribers (see `event`).
+    events: broadcast::Sender<TicketEvent>,
+    /// Set on shutdown, ends the event subscriptions.
+    closing: Arc<watch::Sender<bool>>,
+}
+
+// Construtor
+impl ModelController {
+    /// `db_url` selects the store backend (in-memory mock store when `None`).
+    pub async fn new(db_url: Option<&str>) -> Result<Self> {
+        Ok(Self::new_with_store(store::new_store(db_url).await?))
+    }
+
+    /// e.g., a store shared with the test code.
+    pub fn new_with_store(store: Arc<dyn Store>) -> Self {
+        let (events, _) = broadcast::channel(event::TICKET_EVENTS_CAPACITY);
+        let (closing, _) = watch::channel(false);
+
+        Self {
+            store,
+            events,
+            closing: Arc::new(closing),
+        }
+    }
+}
+
+// Health
+impl ModelController {
+    /// Fails if the store is not reachable.
+    pub async fn ping_store(&self) -> Result<()> {
+        self.store.ping().await
+    }
+}
+
+// Shutdown
+impl ModelController {
+    /// Ends the event subscriptions, so their streaming responses complete.
+    pub fn end_subscriptions(&self) {
+        self.closing.send_replace(true);
+    }
+
+    /// Writes the pending changes and closes the store (once no more requests are served).
+    /// Returns whether the data is persisted (`false` for the in-memory stores).
+    pub async fn close(&self) -> Result<bool> {
+        self.store.close().await?;
+
+        Ok(self.store.is_persistent())
+    }
+}
+
+// CRUD Implementation
+impl ModelController {
+    pub async fn create_ticket(&self, ctx: Ctx, ticket_fc: TicketForCreate) -> Result<Ticket> {
+        ctx.require(Permission::TicketCreate)?;
+
+        let ticket = self
+            .store
+            .ticket_insert(ctx.user_id(), ticket_fc.title)
+            .await?;
+        AuditBmc::record(self, &ctx, AuditAction::Create, None, Some(&ticket)).await;
+        TicketEventBmc::publish(self, TicketEventKind::Created, &ticket);
+
+        Ok(ticket)
+    }
+
+    pub async fn get_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let ticket = self.ticket_for_read(&ctx, id).await?;
+        Ok(ticket)
+    }
+
+    /// Ticket admins see all tickets, other users the ones they created or that are shared with them.
+    pub async fn list_tickets(
+        &self,
+        ctx: Ctx,
+        filter: TicketFilter,
+        list_options: ListOptions,
+    ) -> Result<Page<Ticket>> {
+        ctx.require(Permission::TicketRead)?;
+
+        let visible_to = (!ctx.has_permission(Permission::TicketAdmin)).then_some(ctx.user_id());
+        let (tickets, total) = self
+            .store
+            .ticket_list(visible_to, false, &filter, &list_options)
+            .await?;
+
+        Ok(Page::new(tickets, total, &list_options))
+    }
+
+    pub async fn update_ticket(
+        &self,
+        ctx: Ctx,
+        id: u64,
+        ticket_fu: TicketForUpdate,
+    ) -> Result<Ticket> {
+        let before = self
+            .ticket_for_write(&ctx, id, Permission::TicketUpdate)
+            .await?;
+
+        let ticket = self
+            .store
+            .ticket_update(id, ticket_fu)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+        AuditBmc::record(
+            self,
+            &ctx,
+            AuditAction::Update,
+            Some(&before),
+            Some(&ticket),
+        )
+        .await;
+        TicketEventBmc::publish(self, TicketEventKind::Updated, &ticket);
+
+        Ok(ticket)
+    }
+
+    /// Fails with `Error::TicketTransitionInvalid` if not allowed by the workflow
+    /// (see `TicketStatus::can_transition_to`).
+    pub async fn transition_ticket(
+        &self,
+        ctx: Ctx,
+        id: u64,
+        ticket_ft: TicketForTransition,
+    ) -> Result<Ticket> {
+        let ticket = self
+            .ticket_for_write(&ctx, id, Permission::TicketUpdate)
+            .await?;
+
+        let (from, to) = (ticket.status, ticket_ft.status);
+        if !from.can_transition_to(to) {
+            return Err(Error::TicketTransitionInvalid { id, from, to });
+        }
+
+        // Only if still in the `from` status (concurrent transition otherwise).
+        match self.store.ticket_set_status(id, from, to).await? {
+            Some(after) => {
+                AuditBmc::record(
+                    self,
+                    &ctx,
+                    AuditAction::Transition,
+                    Some(&ticket),
+                    Some(&after),
+                )
+                .await;
+                TicketEventBmc::publish(self, TicketEventKind::Updated, &after);
+                Ok(after)
+            }
+            None => {
+                let ticket = self.store.ticket_get(id).await?;
+                match ticket.filter(|t| t.deleted_at.is_none()) {
+                    Some(ticket) => Err(Error::TicketTransitionInvalid {
+                        id,
+                        from: ticket.status,
+                        to,
+                    }),
+                    None => Err(Error::TicketNotFound { id }),
+                }
+            }
+        }
+    }
+
+    /// Moves the ticket to the trash (see `restore_ticket` and `purge_trash`).
+    pub async fn delete_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let before = self
+            .ticket_for_write(&ctx, id, Permission::TicketDelete)
+            .await?;
+
+        let ticket = self
+            .store
+            .ticket_soft_delete(id, now_unix_sec())
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+        AuditBmc::record(
+            self,
+            &ctx,
+            AuditAction::Delete,
+            Some(&before),
+            Some(&ticket),
+        )
+        .await;
+        TicketEventBmc::publish(self, TicketEventKind::Deleted, &ticket);
+
+        Ok(ticket)
+    }
+}
+
+// Ticket Trash
+impl ModelController {
+    /// Ticket admins see the whole trash, other users the tickets they created.
+    pub async fn list_trash(&self, ctx: Ctx, list_options: ListOptions) -> Result<Page<Ticket>> {
+        ctx.require(Permission::TicketDelete)?;
+
+        let filter = TicketFilter {
+            cid: (!ctx.has_permission(Permission::TicketAdmin)).then_some(ctx.user_id()),
+            ..Default::default()
+        };
+        let (tickets, total) = self
+            .store
+            .ticket_list(None, true, &filter, &list_options)
+            .await?;
+
+        Ok(Page::new(tickets, total, &list_options))
+    }
+
+    pub async fn restore_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let before = self.ticket_for_restore(&ctx, id).await?;
+
+        let ticket = self
+            .store
+            .ticket_restore(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+        AuditBmc::record(
+            self,
+            &ctx,
+            AuditAction::Restore,
+            Some(&before),
+            Some(&ticket),
+        )
+        .await;
+        TicketEventBmc::publish(self, TicketEventKind::Restored, &ticket);
+
+        Ok(ticket)
+    }
+
+    /// Permanently removes the tickets in the trash for more than `retention`
+    /// (with their shares and comments). Returns the number of purged tickets.
+    pub async fn purge_trash(&self, retention: Duration) -> Result<usize> {
+        let deleted_before = now_unix_sec().saturating_sub(retention.as_secs());
+
+        let tickets = self.store.ticket_purge(deleted_before).await?;
+        for ticket in tickets.iter() {
+            AuditBmc::record_system(self, AuditAction::Purge, Some(ticket), None).await;
+        }
+
+        Ok(tickets.len())
+    }
+}
+
+/// Background task running `purge_trash` every `interval`.
+pub fn spawn_trash_purge(
+    mc: ModelController,
+    retention: Duration,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval.max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            match mc.purge_trash(retention).await {
+                Ok(0) => (),
+                Ok(count) => info!("{:<12} - purge_trash - {count} tickets", "TRASH"),
+                Err(ex) => error!("{:<12} - purge_trash - error: {ex:?}", "TRASH"),
+            }
+        }
+    })
+}
+
+// Ticket Shares
+impl ModelController {
+    /// Returns the user ids the ticket is shared with.
+    pub async fn list_ticket_shares(&self, ctx: Ctx, id: u64) -> Result<Vec<u64>> {
+        self.ticket_for_read(&ctx, id).await?;
+        self.store.ticket_share_list(id).await
+    }
+
+    pub async fn share_ticket(&self, ctx: Ctx, id: u64, user_id: u64) -> Result<Vec<u64>> {
+        self.ticket_for_write(&ctx, id, Permission::TicketUpdate)
+            .await?;
+        user::UserBmc::get(self, user_id).await?;
+
+        self.store.ticket_share_add(id, user_id).await?;
+        self.store.ticket_share_list(id).await
+    }
+
+    pub async fn unshare_ticket(&self, ctx: Ctx, id: u64, user_id: u64) -> Result<Vec<u64>> {
+        self.ticket_for_write(&ctx, id, Permission::TicketUpdate)
+            .await?;
+
+        self.store.ticket_share_remove(id, user_id).await?;
+        self.store.ticket_share_list(id).await
+    }
+}
+
+// region:    --- Access Control
+impl ModelController {
+    /// Readable by ticket admins, the creator, and the users it is shared with.
+    /// (tickets in the trash are not found)
+    async fn ticket_for_read(&self, ctx: &Ctx, id: u64) -> Result<Ticket> {
+        ctx.require(Permission::TicketRead)?;
+
+        let ticket = self
+            .store
+            .ticket_get(id)
+            .await?
+            .filter(|t| t.deleted_at.is_none())
+            .ok_or(Error::TicketNotFound { id })?;
+
+        if ctx.has_permission(Permission::TicketAdmin) || ticket.cid == ctx.user_id() {
+            return Ok(ticket);
+        }
+
+        let shared_with = self.store.ticket_share_list(id).await?;
+        if shared_with.contains(&ctx.user_id()) {
+            Ok(ticket)
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    /// Writable (update, delete, share) by ticket admins and the creator only,
+    /// given they also have the `permission` for the operation.
+    async fn ticket_for_write(&self, ctx: &Ctx, id: u64, permission: Permission) -> Result<Ticket> {
+        ctx.require(permission)?;
+
+        let ticket = self
+            .store
+            .ticket_get(id)
+            .await?
+            .filter(|t| t.deleted_at.is_none())
+            .ok_or(Error::TicketNotFound { id })?;
+
+        if ctx.has_permission(Permission::TicketAdmin) || ticket.cid == ctx.user_id() {
+            Ok(ticket)
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    /// Restorable by ticket admins and the creator, like the delete.
+    /// (tickets not in the trash are not found)
+    async fn ticket_for_restore(&self, ctx: &Ctx, id: u64) -> Result<Ticket> {
+        ctx.require(Permission::TicketDelete)?;
+
+        let ticket = self
+            .store
+            .ticket_get(id)
+            .await?
+            .filter(|t| t.deleted_at.is_some())
+            .ok_or(Error::TicketNotFound { id })?;
+
+        if ctx.has_permission(Permission::TicketAdmin) || ticket.cid == ctx.user_id() {
+            Ok(ticket)
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+}
+// endregion: --- Access Control
+// endregion: --- Model Controller
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticket_status_transitions() {
+        use TicketStatus::*;
+
+        assert!(Open.can_transition_to(InProgress));
+        assert!(InProgress.can_transition_to(Resolved));
+        assert!(Resolved.can_transition_to(Closed));
+        assert!(Closed.can_transition_to(Open));
+
+        assert!(!Closed.can_transition_to(Resolved));
+        assert!(!Blocked.can_transition_to(Closed));
+        assert!(!Open.can_transition_to(Open));
+
+        assert_eq!("in-progress".parse::<TicketStatus>().unwrap(), InProgress);
+        assert_eq!(InProgress.as_ref(), "in-progress");
+        assert!("done".parse::<TicketStatus>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ticket_trash() -> Result<()> {
+        use crate::{model::user::Role, web::mw_req_id::ReqId};
+
+        let mc = ModelController::new(None).await?;
+        let ctx = || {
+            Ctx::new(
+                ReqId::new(),
+                1,
+                Role::Member,
+                Role::Member.permissions().to_vec(),
+            )
+        };
+        let ticket_fc = |title: &str| TicketForCreate {
+            title: title.to_string(),
+        };
+        let id_1 = mc.create_ticket(ctx(), ticket_fc("one")).await?.id;
+        let id_2 = mc.create_ticket(ctx(), ticket_fc("two")).await?.id;
+
+        // -- Delete, out of the list and in the trash.
+        let ticket = mc.delete_ticket(ctx(), id_1).await?;
+        assert!(ticket.deleted_at.is_some());
+        assert!(matches!(
+            mc.get_ticket(ctx(), id_1).await,
+            Err(Error::TicketNotFound { .. })
+        ));
+        let page = mc
+            .list_tickets(ctx(), TicketFilter::default(), ListOptions::default())
+            .await?;
+        assert_eq!(page.total, 1);
+        let trash = mc.list_trash(ctx(), ListOptions::default()).await?;
+        assert_eq!(trash.items[0].id, id_1);
+
+        // -- Restore.
+        mc.restore_ticket(ctx(), id_1).await?;
+        mc.get_ticket(ctx(), id_1).await?;
+        assert!(mc.restore_ticket(ctx(), id_1).await.is_err());
+
+        // -- Purge, only past the retention.
+        mc.delete_ticket(ctx(), id_2).await?;
+        assert_eq!(mc.purge_trash(Duration::from_secs(3600)).await?, 0);
+        mc.store.ticket_soft_delete(id_1, 0).await?;
+        assert_eq!(mc.purge_trash(Duration::from_secs(3600)).await?, 1);
+        assert!(mc.store.ticket_get(id_1).await?.is_none());
+        assert_eq!(mc.list_trash(ctx(), ListOptions::default()).await?.total, 1);
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,20 @@
+//! Auth sessions - server-side revocation of the auth tokens (by token id).
+
+use crate::Result;
+
+use super::ModelController;
+
+// region:    --- Session Bmc
+pub struct SessionBmc;
+
+impl SessionBmc {
+    /// Revokes the session `token_id` until `until` (unix sec).
+    pub async fn revoke(mc: &ModelController, token_id: &str, until: u64) -> Result<()> {
+        mc.store.token_deny_add(token_id, until).await
+    }
+
+    pub async fn is_revoked(mc: &ModelController, token_id: &str) -> Result<bool> {
+        mc.store.token_deny_contains(token_id).await
+    }
+}
+// endregion: --- Session Bmc
@@ -0,0 +1,516 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    crypt::token::now_unix_sec,
+    model::{
+        api_key::ApiKeyForAuth,
+        audit::{AuditEvent, AuditEventForCreate, AuditFilter},
+        comment::Comment,
+        user::{Role, UserForAuth},
+        ListOptions, Ticket, TicketFilter, TicketForUpdate, TicketSort, TicketStatus,
+    },
+    Error, Result,
+};
+
+use super::Store;
+
+/// In-memory mock store.
+/// Ticket id is the index in the Vec + 1, ids start at 1 like the sql store
+/// (purged tickets leave a `None`).
+#[derive(Default)]
+pub struct MemStore {
+    tickets: Mutex<Vec<Option<Ticket>>>,
+    ticket_shares: Mutex<Vec<(u64, u64)>>, // (ticket_id, user_id)
+    comments: Mutex<Vec<Option<Comment>>>, // id is the index + 1, like tickets
+    audit_events: Mutex<Vec<AuditEvent>>,  // id is the index + 1
+    users: Mutex<Vec<UserForAuth>>,
+    token_denylist: Mutex<HashMap<String, u64>>, // token_id -> until
+    api_keys: Mutex<Vec<ApiKeyForAuth>>,
+}
+
+impl MemStore {
+    fn tickets(&self) -> Result<MutexGuard<'_, Vec<Option<Ticket>>>> {
+        self.tickets.lock().map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn ticket_shares(&self) -> Result<MutexGuard<'_, Vec<(u64, u64)>>> {
+        self.ticket_shares
+            .lock()
+            .map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn comments(&self) -> Result<MutexGuard<'_, Vec<Option<Comment>>>> {
+        self.comments.lock().map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn audit_events(&self) -> Result<MutexGuard<'_, Vec<AuditEvent>>> {
+        self.audit_events
+            .lock()
+            .map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn users(&self) -> Result<MutexGuard<'_, Vec<UserForAuth>>> {
+        self.users.lock().map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn api_keys(&self) -> Result<MutexGuard<'_, Vec<ApiKeyForAuth>>> {
+        self.api_keys.lock().map_err(|_| Error::StoreLockPoisoned)
+    }
+
+    fn token_denylist(&self) -> Result<MutexGuard<'_, HashMap<String, u64>>> {
+        self.token_denylist
+            .lock()
+            .map_err(|_| Error::StoreLockPoisoned)
+    }
+}
+
+#[async_trait]
+impl Store for MemStore {
+    /// Always reachable, only fails on a poisoned lock.
+    async fn ping(&self) -> Result<()> {
+        let _tickets = self.tickets()?;
+        Ok(())
+    }
+
+    /// Nothing to write, the mock store is not persisted.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    // region:    --- Tickets
+    async fn ticket_insert(&self, cid: u64, title: String) -> Result<Ticket> {
+        let mut store = self.tickets()?;
+
+        let id = next_id(&store);
+        let ticket = Ticket {
+            id,
+            cid,
+            title,
+            status: TicketStatus::default(),
+            deleted_at: None,
+        };
+        store.push(Some(ticket.clone()));
+
+        Ok(ticket)
+    }
+
+    async fn ticket_get(&self, id: u64) -> Result<Option<Ticket>> {
+        let store = self.tickets()?;
+
+        Ok(entry(&store, id).and_then(|t| t.clone()))
+    }
+
+    async fn ticket_list(
+        &self,
+        visible_to: Option<u64>,
+        deleted: bool,
+        filter: &TicketFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<Ticket>, u64)> {
+        let store = self.tickets()?;
+        let shares = self.ticket_shares()?;
+
+        let title = filter.title.as_ref().map(|t| t.to_lowercase());
+        let mut tickets: Vec<Ticket> = store
+            .iter()
+            .flatten()
+            .filter(|t| t.deleted_at.is_some() == deleted)
+            .filter(|t| match visible_to {
+                None => true,
+                Some(user_id) => t.cid == user_id || shares.contains(&(t.id, user_id)),
+            })
+            .filter(|t| filter.cid.is_none_or(|cid| t.cid == cid))
+            .filter(|t| filter.status.is_none_or(|status| t.status == status))
+            .filter(|t| {
+                title
+                    .as_ref()
+                    .is_none_or(|title| t.title.to_lowercase().contains(title))
+            })
+            .cloned()
+            .collect();
+
+        match list_options.sort.unwrap_or_default() {
+            TicketSort::IdAsc => tickets.sort_by_key(|t| t.id),
+            TicketSort::IdDesc => tickets.sort_by_key(|t| std::cmp::Reverse(t.id)),
+            TicketSort::TitleAsc => {
+                tickets.sort_by(|a, b| a.title.cmp(&b.title).then(a.id.cmp(&b.id)))
+            }
+            TicketSort::TitleDesc => {
+                tickets.sort_by(|a, b| b.title.cmp(&a.title).then(b.id.cmp(&a.id)))
+            }
+        }
+
+        let total = tickets.len() as u64;
+        let tickets = tickets
+            .into_iter()
+            .skip(usize::try_from(list_options.offset()).unwrap_or(usize::MAX))
+            .take(list_options.limit() as usize)
+            .collect();
+
+        Ok((tickets, total))
+    }
+
+    async fn ticket_update(&self, id: u64, ticket_fu: TicketForUpdate) -> Result<Option<Ticket>> {
+        let mut store = self.tickets()?;
+
+        let Some(ticket) = live_ticket_mut(&mut store, id) else {
+            return Ok(None);
+        };
+        if let Some(title) = ticket_fu.title {
+            ticket.title = title;
+        }
+
+        Ok(Some(ticket.clone()))
+    }
+
+    async fn ticket_set_status(
+        &self,
+        id: u64,
+        from: TicketStatus,
+        to: TicketStatus,
+    ) -> Result<Option<Ticket>> {
+        let mut store = self.tickets()?;
+
+        let Some(ticket) = live_ticket_mut(&mut store, id).filter(|t| t.status == from) else {
+            return Ok(None);
+        };
+        ticket.status = to;
+
+        Ok(Some(ticket.clone()))
+    }
+
+    async fn ticket_soft_delete(&self, id: u64, now: u64) -> Result<Option<Ticket>> {
+        let mut store = self.tickets()?;
+
+        let Some(ticket) = live_ticket_mut(&mut store, id) else {
+            return Ok(None);
+        };
+        ticket.deleted_at = Some(now);
+
+        Ok(Some(ticket.clone()))
+    }
+
+    async fn ticket_restore(&self, id: u64) -> Result<Option<Ticket>> {
+        let mut store = self.tickets()?;
+
+        let Some(ticket) = entry_mut(&mut store, id)
+            .and_then(|t| t.as_mut())
+            .filter(|t| t.deleted_at.is_some())
+        else {
+            return Ok(None);
+        };
+        ticket.deleted_at = None;
+
+        Ok(Some(ticket.clone()))
+    }
+
+    async fn ticket_purge(&self, deleted_before: u64) -> Result<Vec<Ticket>> {
+        let mut store = self.tickets()?;
+
+        let purged: Vec<Ticket> = store
+            .iter_mut()
+            .filter(|t| {
+                t.as_ref()
+                    .and_then(|t| t.deleted_at)
+                    .is_some_and(|deleted_at| deleted_at < deleted_before)
+            })
+            .filter_map(|t| t.take())
+            .collect();
+        if !purged.is_empty() {
+            let is_purged = |ticket_id: u64| purged.iter().any(|t| t.id == ticket_id);
+            self.ticket_shares()?
+                .retain(|(ticket_id, _)| !is_purged(*ticket_id));
+            for comment in self.comments()?.iter_mut() {
+                if comment.as_ref().is_some_and(|c| is_purged(c.ticket_id)) {
+                    *comment = None;
+                }
+            }
+        }
+
+        Ok(purged)
+    }
+    // endregion: --- Tickets
+
+    // region:    --- Ticket Shares
+    async fn ticket_share_add(&self, ticket_id: u64, user_id: u64) -> Result<()> {
+        let mut shares = self.ticket_shares()?;
+
+        if !shares.contains(&(ticket_id, user_id)) {
+            shares.push((ticket_id, user_id));
+        }
+
+        Ok(())
+    }
+
+    async fn ticket_share_remove(&self, ticket_id: u64, user_id: u64) -> Result<()> {
+        let mut shares = self.ticket_shares()?;
+        shares.retain(|share| *share != (ticket_id, user_id));
+
+        Ok(())
+    }
+
+    async fn ticket_share_list(&self, ticket_id: u64) -> Result<Vec<u64>> {
+        let shares = self.ticket_shares()?;
+        let user_ids = shares
+            .iter()
+            .filter(|(t_id, _)| *t_id == ticket_id)
+            .map(|(_, user_id)| *user_id)
+            .collect();
+
+        Ok(user_ids)
+    }
+    // endregion: --- Ticket Shares
+
+    // region:    --- Ticket Comments
+    async fn comment_insert(
+        &self,
+        ticket_id: u64,
+        cid: u64,
+        body: String,
+        now: u64,
+    ) -> Result<Comment> {
+        let mut store = self.comments()?;
+
+        let comment = Comment {
+            id: next_id(&store),
+            ticket_id,
+            cid,
+            body,
+            ctime: now,
+            mtime: now,
+        };
+        store.push(Some(comment.clone()));
+
+        Ok(comment)
+    }
+
+    async fn comment_get(&self, id: u64) -> Result<Option<Comment>> {
+        let store = self.comments()?;
+
+        Ok(entry(&store, id).and_then(|c| c.clone()))
+    }
+
+    async fn comment_list(&self, ticket_id: u64) -> Result<Vec<Comment>> {
+        let store = self.comments()?;
+
+        Ok(store
+            .iter()
+            .flatten()
+            .filter(|c| c.ticket_id == ticket_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn comment_update(&self, id: u64, body: String, now: u64) -> Result<Option<Comment>> {
+        let mut store = self.comments()?;
+
+        let Some(comment) = entry_mut(&mut store, id).and_then(|c| c.as_mut()) else {
+            return Ok(None);
+        };
+        comment.body = body;
+        comment.mtime = now;
+
+        Ok(Some(comment.clone()))
+    }
+
+    async fn comment_delete(&self, id: u64) -> Result<Option<Comment>> {
+        let mut store = self.comments()?;
+
+        Ok(entry_mut(&mut store, id).and_then(|c| c.take()))
+    }
+    // endregion: --- Ticket Comments
+
+    // region:    --- Audit
+    async fn audit_insert(&self, event: AuditEventForCreate) -> Result<AuditEvent> {
+        let mut store = self.audit_events()?;
+
+        let AuditEventForCreate {
+            ticket_id,
+            actor_id,
+            action,
+            diff,
+            ctime,
+        } = event;
+        let event = AuditEvent {
+            id: next_id(&store),
+            ticket_id,
+            actor_id,
+            action,
+            diff,
+            ctime,
+        };
+        store.push(event.clone());
+
+        Ok(event)
+    }
+
+    async fn audit_list(
+        &self,
+        filter: &AuditFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<AuditEvent>, u64)> {
+        let store = self.audit_events()?;
+
+        let events: Vec<&AuditEvent> = store
+            .iter()
+            .rev()
+            .filter(|e| filter.ticket_id.is_none_or(|id| e.ticket_id == id))
+            .filter(|e| filter.actor_id.is_none_or(|id| e.actor_id == id))
+            .collect();
+
+        let total = events.len() as u64;
+        let events = events
+            .into_iter()
+            .skip(usize::try_from(list_options.offset()).unwrap_or(usize::MAX))
+            .take(list_options.limit() as usize)
+            .cloned()
+            .collect();
+
+        Ok((events, total))
+    }
+    // endregion: --- Audit
+
+    // region:    --- Users
+    async fn user_insert(&self, username: String, pwd_hash: String) -> Result<UserForAuth> {
+        let mut store = self.users()?;
+
+        if store.iter().any(|u| u.username == username) {
+            return Err(Error::UserAlreadyExists { username });
+        }
+
+        let user = UserForAuth {
+            id: next_id(&store),
+            username,
+            pwd_hash,
+            role: Role::default(),
+        };
+        store.push(user.clone());
+
+        Ok(user)
+    }
+
+    async fn user_get(&self, id: u64) -> Result<Option<UserForAuth>> {
+        let store = self.users()?;
+
+        Ok(store.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn user_list(&self) -> Result<Vec<UserForAuth>> {
+        let store = self.users()?;
+
+        Ok(store.clone())
+    }
+
+    async fn user_first_by_username(&self, username: &str) -> Result<Option<UserForAuth>> {
+        let store = self.users()?;
+
+        Ok(store.iter().find(|u| u.username == username).cloned())
+    }
+
+    async fn user_update_pwd(&self, id: u64, pwd_hash: String) -> Result<()> {
+        let mut store = self.users()?;
+
+        let user = store
+            .iter_mut()
+            .find(|u| u.id == id)
+            .ok_or(Error::UserNotFound { id })?;
+        user.pwd_hash = pwd_hash;
+
+        Ok(())
+    }
+
+    async fn user_update_role(&self, id: u64, role: Role) -> Result<()> {
+        let mut store = self.users()?;
+
+        let user = store
+            .iter_mut()
+            .find(|u| u.id == id)
+            .ok_or(Error::UserNotFound { id })?;
+        user.role = role;
+
+        Ok(())
+    }
+    // endregion: --- Users
+
+    // region:    --- Token Denylist
+    async fn token_deny_add(&self, token_id: &str, until: u64) -> Result<()> {
+        let mut denylist = self.token_denylist()?;
+
+        let now = now_unix_sec();
+        denylist.retain(|_, until| *until > now);
+        denylist.insert(token_id.to_string(), until);
+
+        Ok(())
+    }
+
+    async fn token_deny_contains(&self, token_id: &str) -> Result<bool> {
+        let denylist = self.token_denylist()?;
+
+        Ok(denylist.contains_key(token_id))
+    }
+    // endregion: --- Token Denylist
+
+    // region:    --- Api Keys
+    async fn api_key_insert(&self, api_key: ApiKeyForAuth) -> Result<()> {
+        let mut store = self.api_keys()?;
+        store.push(api_key);
+
+        Ok(())
+    }
+
+    async fn api_key_get(&self, id: &str) -> Result<Option<ApiKeyForAuth>> {
+        let store = self.api_keys()?;
+
+        Ok(store.iter().find(|k| k.api_key.id == id).cloned())
+    }
+
+    async fn api_key_list(&self, user_id: u64) -> Result<Vec<ApiKeyForAuth>> {
+        let store = self.api_keys()?;
+
+        Ok(store
+            .iter()
+            .filter(|k| k.api_key.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn api_key_delete(&self, id: &str) -> Result<bool> {
+        let mut store = self.api_keys()?;
+
+        let len = store.len();
+        store.retain(|k| k.api_key.id != id);
+
+        Ok(store.len() < len)
+    }
+    // endregion: --- Api Keys
+}
+
+/// Id of the next pushed entry (ids start at 1, 0 is never a valid id).
+fn next_id<T>(store: &[T]) -> u64 {
+    store.len() as u64 + 1
+}
+
+/// The entry of `id` (at index `id - 1`).
+fn entry<T>(store: &[T], id: u64) -> Option<&T> {
+    store.get((id as usize).checked_sub(1)?)
+}
+
+fn entry_mut<T>(store: &mut [T], id: u64) -> Option<&mut T> {
+    store.get_mut((id as usize).checked_sub(1)?)
+}
+
+/// The ticket `id`, if not in the trash.
+fn live_ticket_mut(store: &mut [Option<Ticket>], id: u64) -> Option<&mut Ticket> {
+    entry_mut(store, id)
+        .and_then(|t| t.as_mut())
+        .filter(|t| t.deleted_at.is_none())
+}
@@ -0,0 +1,389 @@
+//! Storage backends for the ModelController
+//! - `MemStore` - in-memory mock store (default, lost on restart).
+//! - `SqlStore` - SQLite (local/test) or Postgres (prod), selected by the db url.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::Result;
+
+use super::{
+    api_key::ApiKeyForAuth,
+    audit::{AuditEvent, AuditEventForCreate, AuditFilter},
+    comment::Comment,
+    user::{Role, UserForAuth},
+    ListOptions, Ticket, TicketFilter, TicketForUpdate, TicketStatus,
+};
+
+pub use self::mem::MemStore;
+pub use self::sql::SqlStore;
+
+mod mem;
+mod sql;
+
+/// Low-level storage operations. No authorization here, this is the job of the
+/// `ModelController` / Bmc layer on top of it.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Fails if the backend is not reachable (for the readiness check).
+    async fn ping(&self) -> Result<()>;
+    /// Writes the pending changes and releases the backend (on shutdown).
+    async fn close(&self) -> Result<()>;
+    /// Whether the data outlives the process (not for the in-memory stores).
+    fn is_persistent(&self) -> bool;
+
+    // -- Tickets
+    async fn ticket_insert(&self, cid: u64, title: String) -> Result<Ticket>;
+    /// Also returns the tickets in the trash (see `Ticket::deleted_at`).
+    async fn ticket_get(&self, id: u64) -> Result<Option<Ticket>>;
+    /// `visible_to` - only tickets created by or shared with this user (`None` for all).
+    /// `deleted` - the tickets in the trash instead of the live ones.
+    /// Returns the requested page and the total number of matching tickets.
+    async fn ticket_list(
+        &self,
+        visible_to: Option<u64>,
+        deleted: bool,
+        filter: &TicketFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<Ticket>, u64)>;
+    /// Returns the updated ticket, `None` if no live ticket with this id.
+    async fn ticket_update(&self, id: u64, ticket_fu: TicketForUpdate) -> Result<Option<Ticket>>;
+    /// Sets the status only if it is still `from` (compare-and-set).
+    /// Returns the updated ticket, `None` if no live ticket with this id and status.
+    async fn ticket_set_status(
+        &self,
+        id: u64,
+        from: TicketStatus,
+        to: TicketStatus,
+    ) -> Result<Option<Ticket>>;
+    /// Moves the ticket to the trash (sets `deleted_at` to `now`).
+    /// Returns the deleted ticket, `None` if no live ticket with this id.
+    async fn ticket_soft_delete(&self, id: u64, now: u64) -> Result<Option<Ticket>>;
+    /// Returns the restored ticket, `None` if no ticket with this id in the trash.
+    async fn ticket_restore(&self, id: u64) -> Result<Option<Ticket>>;
+    /// Permanently removes the tickets deleted before `deleted_before` (unix sec),
+    /// with their shares and comments. Returns the purged tickets.
+    async fn ticket_purge(&self, deleted_before: u64) -> Result<Vec<Ticket>>;
+
+    // -- Ticket Shares
+    /// No-op if already shared.
+    async fn ticket_share_add(&self, ticket_id: u64, user_id: u64) -> Result<()>;
+    async fn ticket_share_remove(&self, ticket_id: u64, user_id: u64) -> Result<()>;
+    /// Returns the user ids the ticket is shared with.
+    async fn ticket_share_list(&self, ticket_id: u64) -> Result<Vec<u64>>;
+
+    // -- Ticket Comments
+    async fn comment_insert(
+        &self,
+        ticket_id: u64,
+        cid: u64,
+        body: String,
+        now: u64,
+    ) -> Result<Comment>;
+    async fn comment_get(&self, id: u64) -> Result<Option<Comment>>;
+    /// The comments of `ticket_id`, oldest first.
+    async fn comment_list(&self, ticket_id: u64) -> Result<Vec<Comment>>;
+    /// Returns the updated comment, `None` if no comment with this id.
+    async fn comment_update(&self, id: u64, body: String, now: u64) -> Result<Option<Comment>>;
+    /// Returns the deleted comment, `None` if no comment with this id.
+    async fn comment_delete(&self, id: u64) -> Result<Option<Comment>>;
+
+    // -- Audit
+    async fn audit_insert(&self, event: AuditEventForCreate) -> Result<AuditEvent>;
+    /// Newest first (`list_options.sort` is ignored).
+    /// Returns the requested page and the total number of matching events.
+    async fn audit_list(
+        &self,
+        filter: &AuditFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<AuditEvent>, u64)>;
+
+    // -- Users
+    /// Fails with `Error::UserAlreadyExists` if the username is taken.
+    async fn user_insert(&self, username: String, pwd_hash: String) -> Result<UserForAuth>;
+    async fn user_get(&self, id: u64) -> Result<Option<UserForAuth>>;
+    async fn user_list(&self) -> Result<Vec<UserForAuth>>;
+    async fn user_first_by_username(&self, username: &str) -> Result<Option<UserForAuth>>;
+    async fn user_update_pwd(&self, id: u64, pwd_hash: String) -> Result<()>;
+    async fn user_update_role(&self, id: u64, role: Role) -> Result<()>;
+
+    // -- Token Denylist
+    /// Revokes the token id until `until` (unix sec, the latest expiration a
+    /// token with this id can have). Also purges the entries past their `until`.
+    async fn token_deny_add(&self, token_id: &str, until: u64) -> Result<()>;
+    async fn token_deny_contains(&self, token_id: &str) -> Result<bool>;
+
+    // -- Api Keys
+    async fn api_key_insert(&self, api_key: ApiKeyForAuth) -> Result<()>;
+    async fn api_key_get(&self, id: &str) -> Result<Option<ApiKeyForAuth>>;
+    /// The keys of `user_id`, oldest first.
+    async fn api_key_list(&self, user_id: u64) -> Result<Vec<ApiKeyForAuth>>;
+    /// Returns `false` if no key with this id.
+    async fn api_key_delete(&self, id: &str) -> Result<bool>;
+}
+
+/// Build the store for `db_url`, in-memory when `None`.
+/// Supported urls: `sqlite:...` and `postgres://...`.
+pub async fn new_store(db_url: Option<&str>) -> Result<Arc<dyn Store>> {
+    match db_url {
+        None => Ok(Arc::new(MemStore::default())),
+        Some(db_url) => Ok(Arc::new(SqlStore::connect(db_url).await?)),
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::{audit::AuditAction, TicketSort};
+
+    /// The same cases run against each store (named for the assert messages).
+    async fn stores() -> Result<Vec<(&'static str, Arc<dyn Store>)>> {
+        Ok(vec![
+            ("mem", Arc::new(MemStore::default())),
+            ("sql", Arc::new(SqlStore::connect("sqlite::memory:").await?)),
+        ])
+    }
+
+    fn titles(tickets: &[Ticket]) -> Vec<&str> {
+        tickets.iter().map(|t| t.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_store_ticket_crud() -> Result<()> {
+        for (name, store) in stores().await? {
+            // Both in-memory.
+            assert!(!store.is_persistent(), "{name}");
+
+            // -- Insert, ids start at 1.
+            let ticket = store.ticket_insert(7, "one".to_string()).await?;
+            assert_eq!((ticket.id, ticket.cid), (1, 7), "{name}");
+            assert_eq!(ticket.status, TicketStatus::Open, "{name}");
+            store.ticket_insert(8, "two".to_string()).await?;
+
+            // -- Get
+            assert_eq!(store.ticket_get(1).await?.unwrap().title, "one", "{name}");
+            assert!(store.ticket_get(0).await?.is_none(), "{name}");
+            assert!(store.ticket_get(99).await?.is_none(), "{name}");
+
+            // -- List
+            let list_options = ListOptions {
+                limit: Some(1),
+                sort: Some(TicketSort::IdDesc),
+                ..Default::default()
+            };
+            let filter = TicketFilter::default();
+            let (tickets, total) = store
+                .ticket_list(None, false, &filter, &list_options)
+                .await?;
+            assert_eq!((titles(&tickets), total), (vec!["two"], 2), "{name}");
+            let (tickets, total) = store
+                .ticket_list(Some(7), false, &filter, &ListOptions::default())
+                .await?;
+            assert_eq!((titles(&tickets), total), (vec!["one"], 1), "{name}");
+            let list_options = ListOptions {
+                offset: Some(u64::MAX),
+                ..Default::default()
+            };
+            let (tickets, total) = store
+                .ticket_list(None, false, &filter, &list_options)
+                .await?;
+            assert_eq!((titles(&tickets), total), (vec![], 2), "{name}");
+
+            // -- Update
+            let ticket_fu = TicketForUpdate {
+                title: Some("one v2".to_string()),
+            };
+            let ticket = store.ticket_update(1, ticket_fu).await?.unwrap();
+            assert_eq!(ticket.title, "one v2", "{name}");
+
+            // -- Set status (compare-and-set)
+            use TicketStatus::*;
+            let ticket = store.ticket_set_status(1, Open, Closed).await?;
+            assert_eq!(ticket.unwrap().status, Closed, "{name}");
+            assert!(
+                store.ticket_set_status(1, Open, Closed).await?.is_none(),
+                "{name}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_ticket_title_filter() -> Result<()> {
+        for (name, store) in stores().await? {
+            for title in ["100% Done", "100 done", "a_b", "axb", r"back\slash"] {
+                store.ticket_insert(1, title.to_string()).await?;
+            }
+
+            // The LIKE wildcards (and escape char) match literally.
+            for (title, expected) in [
+                ("100%", vec!["100% Done"]),
+                ("DONE", vec!["100% Done", "100 done"]),
+                ("a_b", vec!["a_b"]),
+                (r"\", vec![r"back\slash"]),
+            ] {
+                let filter = TicketFilter {
+                    title: Some(title.to_string()),
+                    ..Default::default()
+                };
+                let (tickets, _) = store
+                    .ticket_list(None, false, &filter, &ListOptions::default())
+                    .await?;
+                assert_eq!(titles(&tickets), expected, "{name} - {title}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_comments() -> Result<()> {
+        for (name, store) in stores().await? {
+            let comment = store.comment_insert(1, 7, "c1".to_string(), 10).await?;
+            assert_eq!((comment.id, comment.ticket_id), (1, 1), "{name}");
+            store.comment_insert(2, 7, "other".to_string(), 11).await?;
+            store.comment_insert(1, 8, "c2".to_string(), 12).await?;
+
+            // -- List, oldest first.
+            let bodies = |comments: Vec<Comment>| -> Vec<String> {
+                comments.into_iter().map(|c| c.body).collect()
+            };
+            assert_eq!(bodies(store.comment_list(1).await?), ["c1", "c2"], "{name}");
+
+            // -- Update
+            let comment = store.comment_update(1, "c1 v2".to_string(), 20).await?;
+            let comment = comment.unwrap();
+            assert_eq!(
+                (comment.body.as_str(), comment.ctime, comment.mtime),
+                ("c1 v2", 10, 20),
+                "{name}"
+            );
+            assert!(
+                store.comment_update(99, String::new(), 20).await?.is_none(),
+                "{name}"
+            );
+
+            // -- Delete
+            assert_eq!(store.comment_delete(1).await?.unwrap().id, 1, "{name}");
+            assert!(store.comment_delete(1).await?.is_none(), "{name}");
+            assert!(store.comment_get(1).await?.is_none(), "{name}");
+            assert_eq!(bodies(store.comment_list(1).await?), ["c2"], "{name}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_trash_restore_purge() -> Result<()> {
+        for (name, store) in stores().await? {
+            let id = store.ticket_insert(1, "one".to_string()).await?.id;
+            store.ticket_insert(1, "two".to_string()).await?;
+            store.ticket_share_add(id, 2).await?;
+            store.comment_insert(id, 1, "c".to_string(), 1).await?;
+
+            // -- Soft delete, only the live tickets can change.
+            let ticket = store.ticket_soft_delete(id, 10).await?.unwrap();
+            assert_eq!(ticket.deleted_at, Some(10), "{name}");
+            assert!(store.ticket_soft_delete(id, 10).await?.is_none(), "{name}");
+            let ticket_fu = TicketForUpdate { title: None };
+            assert!(
+                store.ticket_update(id, ticket_fu).await?.is_none(),
+                "{name}"
+            );
+            let list = |deleted| {
+                let store = store.clone();
+                async move {
+                    let filter = TicketFilter::default();
+                    let list_options = ListOptions::default();
+                    let (tickets, _) = store
+                        .ticket_list(None, deleted, &filter, &list_options)
+                        .await?;
+                    Result::Ok(tickets.into_iter().map(|t| t.id).collect::<Vec<_>>())
+                }
+            };
+            assert_eq!(list(true).await?, [id], "{name}");
+            assert_eq!(list(false).await?, [2], "{name}");
+
+            // -- Restore, only from the trash.
+            assert!(
+                store
+                    .ticket_restore(id)
+                    .await?
+                    .unwrap()
+                    .deleted_at
+                    .is_none(),
+                "{name}"
+            );
+            assert!(store.ticket_restore(id).await?.is_none(), "{name}");
+            assert!(store.ticket_restore(2).await?.is_none(), "{name}");
+
+            // -- Purge, only the ones deleted before, with their shares and comments.
+            store.ticket_soft_delete(id, 10).await?;
+            assert!(store.ticket_purge(10).await?.is_empty(), "{name}");
+            let purged = store.ticket_purge(11).await?;
+            assert_eq!(
+                purged.iter().map(|t| t.id).collect::<Vec<_>>(),
+                [id],
+                "{name}"
+            );
+            assert!(store.ticket_get(id).await?.is_none(), "{name}");
+            assert!(store.ticket_share_list(id).await?.is_empty(), "{name}");
+            assert!(store.comment_list(id).await?.is_empty(), "{name}");
+            assert!(store.ticket_get(2).await?.is_some(), "{name}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_audit_history() -> Result<()> {
+        for (name, store) in stores().await? {
+            for (ticket_id, actor_id, action) in [
+                (1, 7, AuditAction::Create),
+                (2, 7, AuditAction::Create),
+                (1, 8, AuditAction::Update),
+                (1, 7, AuditAction::Delete),
+            ] {
+                store
+                    .audit_insert(AuditEventForCreate {
+                        ticket_id,
+                        actor_id,
+                        action,
+                        diff: json!({}),
+                        ctime: 1,
+                    })
+                    .await?;
+            }
+
+            // -- Newest first, paged.
+            let filter = AuditFilter {
+                ticket_id: Some(1),
+                ..Default::default()
+            };
+            let list_options = ListOptions {
+                limit: Some(2),
+                ..Default::default()
+            };
+            let (events, total) = store.audit_list(&filter, &list_options).await?;
+            let ids: Vec<u64> = events.iter().map(|e| e.id).collect();
+            assert_eq!((ids, total), (vec![4, 3], 3), "{name}");
+            assert_eq!(events[0].action, AuditAction::Delete, "{name}");
+
+            let filter = AuditFilter {
+                ticket_id: Some(1),
+                actor_id: Some(7),
+            };
+            let (events, total) = store.audit_list(&filter, &ListOptions::default()).await?;
+            let ids: Vec<u64> = events.iter().map(|e| e.id).collect();
+            assert_eq!((ids, total), (vec![4, 1], 2), "{name}");
+        }
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
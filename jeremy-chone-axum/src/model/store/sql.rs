@@ -0,0 +1,889 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{
+    any::{install_default_drivers, AnyArguments, AnyPoolOptions, AnyRow},
+    query::Query,
+    Any, AnyPool, Row,
+};
+use tracing::info;
+
+use crate::{
+    crypt::token::now_unix_sec,
+    model::{
+        api_key::{ApiKey, ApiKeyForAuth},
+        audit::{AuditEvent, AuditEventForCreate, AuditFilter},
+        comment::Comment,
+        user::{Role, UserForAuth},
+        ListOptions, Ticket, TicketFilter, TicketForUpdate, TicketSort, TicketStatus,
+    },
+    Error, Result,
+};
+
+use super::Store;
+
+// region:    --- Migrations
+/// (version, name, sql) - applied in order, once, at startup.
+const MIGRATIONS: &[(i64, &str, &str)] = &[
+    (1, "init", include_str!("../../../migrations/0001_init.sql")),
+    (
+        2,
+        "ticket_share_user_role",
+        include_str!("../../../migrations/0002_ticket_share_user_role.sql"),
+    ),
+    (
+        3,
+        "token_denylist",
+        include_str!("../../../migrations/0003_token_denylist.sql"),
+    ),
+    (
+        4,
+        "api_key",
+        include_str!("../../../migrations/0004_api_key.sql"),
+    ),
+    (
+        5,
+        "ticket_status",
+        include_str!("../../../migrations/0005_ticket_status.sql"),
+    ),
+    (
+        6,
+        "comment",
+        include_str!("../../../migrations/0006_comment.sql"),
+    ),
+    (
+        7,
+        "audit_event",
+        include_str!("../../../migrations/0007_audit_event.sql"),
+    ),
+    (
+        8,
+        "ticket_deleted_at",
+        include_str!("../../../migrations/0008_ticket_deleted_at.sql"),
+    ),
+];
+// endregion: --- Migrations
+
+#[derive(Clone, Copy, Debug)]
+enum Dialect {
+    Sqlite,
+    Postgres,
+}
+
+impl Dialect {
+    fn from_db_url(db_url: &str) -> Result<Self> {
+        if db_url.starts_with("sqlite:") {
+            Ok(Self::Sqlite)
+        } else if db_url.starts_with("postgres:") || db_url.starts_with("postgresql:") {
+            Ok(Self::Postgres)
+        } else {
+            Err(Error::StoreUnsupportedDbUrl)
+        }
+    }
+
+    fn id_pk(&self) -> &'static str {
+        match self {
+            Self::Sqlite => "INTEGER PRIMARY KEY AUTOINCREMENT",
+            Self::Postgres => "BIGSERIAL PRIMARY KEY",
+        }
+    }
+}
+
+/// SQL store (SQLite or Postgres through the sqlx `Any` driver).
+///
+/// NOTE: Queries use `$N` placeholders, which both SQLite and Postgres accept.
+pub struct SqlStore {
+    pool: AnyPool,
+    /// `false` for an in-memory SQLite db.
+    persistent: bool,
+}
+
+impl SqlStore {
+    pub async fn connect(db_url: &str) -> Result<Self> {
+        let dialect = Dialect::from_db_url(db_url)?;
+        install_default_drivers();
+
+        // An in-memory SQLite db lives and dies with its connection,
+        // so keep a single one open forever.
+        let in_memory = db_url.contains(":memory:") || db_url.contains("mode=memory");
+        let pool_options = if in_memory {
+            AnyPoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            AnyPoolOptions::new().max_connections(5)
+        };
+
+        let pool = pool_options
+            .connect(db_url)
+            .await
+            .map_err(|ex| Error::StoreConnectFail(ex.to_string()))?;
+
+        let store = Self {
+            pool,
+            persistent: !in_memory,
+        };
+        store.migrate(dialect).await?;
+
+        Ok(store)
+    }
+
+    async fn migrate(&self, dialect: Dialect) -> Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS _migration (version BIGINT PRIMARY KEY, name TEXT NOT NULL)",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(store_err)?;
+
+        let applied: Vec<i64> = sqlx::query("SELECT version FROM _migration")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(|row| row.try_get::<i64, _>(0))
+            .collect::<core::result::Result<_, _>>()
+            .map_err(store_err)?;
+
+        for (version, name, sql) in MIGRATIONS {
+            if applied.contains(version) {
+                continue;
+            }
+            info!("{:<12} - migrate - {version} {name}", "STORE");
+
+            let migration_err = |ex: sqlx::Error| Error::StoreMigrationFail {
+                version: *version,
+                cause: ex.to_string(),
+            };
+
+            let sql = sql.replace("{{ID_PK}}", dialect.id_pk());
+            let mut tx = self.pool.begin().await.map_err(migration_err)?;
+            sqlx::raw_sql(&sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(migration_err)?;
+            sqlx::query("INSERT INTO _migration (version, name) VALUES ($1, $2)")
+                .bind(*version)
+                .bind(*name)
+                .execute(&mut *tx)
+                .await
+                .map_err(migration_err)?;
+            tx.commit().await.map_err(migration_err)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Ticket columns for `ticket_from_row`.
+const TICKET_COLS: &str = "id, cid, title, status, deleted_at";
+/// Comment columns for `comment_from_row`.
+const COMMENT_COLS: &str = "id, ticket_id, cid, body, ctime, mtime";
+/// Audit event columns for `audit_event_from_row`.
+const AUDIT_EVENT_COLS: &str = "id, ticket_id, actor_id, action, diff, ctime";
+
+#[async_trait]
+impl Store for SqlStore {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(store_err)?;
+        Ok(())
+    }
+
+    /// Waits for the checked out connections (the sqlite WAL is checkpointed on close).
+    async fn close(&self) -> Result<()> {
+        self.pool.close().await;
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    // region:    --- Tickets
+    async fn ticket_insert(&self, cid: u64, title: String) -> Result<Ticket> {
+        let sql =
+            format!("INSERT INTO ticket (cid, title) VALUES ($1, $2) RETURNING {TICKET_COLS}");
+        let row = sqlx::query(&sql)
+            .bind(cid as i64)
+            .bind(&title)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        ticket_from_row(&row)
+    }
+
+    async fn ticket_get(&self, id: u64) -> Result<Option<Ticket>> {
+        let sql = format!("SELECT {TICKET_COLS} FROM ticket WHERE id = $1");
+        sqlx::query(&sql)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(ticket_from_row)
+            .transpose()
+    }
+
+    async fn ticket_list(
+        &self,
+        visible_to: Option<u64>,
+        deleted: bool,
+        filter: &TicketFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<Ticket>, u64)> {
+        // -- Build the where clause.
+        let mut params = Params::default();
+        let deleted_cond = if deleted {
+            "deleted_at IS NOT NULL"
+        } else {
+            "deleted_at IS NULL"
+        };
+        let mut conds: Vec<String> = vec![deleted_cond.to_string()];
+        if let Some(user_id) = visible_to {
+            let p = params.push(SqlValue::Int(user_id as i64));
+            conds.push(format!(
+                "(cid = {p} OR id IN (SELECT ticket_id FROM ticket_share WHERE user_id = {p}))"
+            ));
+        }
+        if let Some(cid) = filter.cid {
+            let p = params.push(SqlValue::Int(cid as i64));
+            conds.push(format!("cid = {p}"));
+        }
+        if let Some(status) = filter.status {
+            let p = params.push(SqlValue::Text(status.as_ref().to_string()));
+            conds.push(format!("status = {p}"));
+        }
+        if let Some(title) = &filter.title {
+            let p = params.push(SqlValue::Text(like_contains(title)));
+            conds.push(format!("LOWER(title) LIKE LOWER({p}) ESCAPE '\\'"));
+        }
+        let where_clause = format!("WHERE {}", conds.join(" AND "));
+
+        // -- Count all matching.
+        let sql = format!("SELECT COUNT(*) AS total FROM ticket {where_clause}");
+        let total = params
+            .bind_all(sqlx::query(&sql))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(store_err)?
+            .try_get::<i64, _>("total")
+            .map_err(store_err)? as u64;
+
+        // -- Fetch the page.
+        let order_by = match list_options.sort.unwrap_or_default() {
+            TicketSort::IdAsc => "id ASC",
+            TicketSort::IdDesc => "id DESC",
+            TicketSort::TitleAsc => "title ASC, id ASC",
+            TicketSort::TitleDesc => "title DESC, id DESC",
+        };
+        let p_limit = params.push(SqlValue::Int(list_options.limit() as i64));
+        let p_offset = params.push(SqlValue::Int(list_options.offset() as i64));
+        let sql = format!(
+            "SELECT {TICKET_COLS} FROM ticket {where_clause} \
+             ORDER BY {order_by} LIMIT {p_limit} OFFSET {p_offset}"
+        );
+        let tickets = params
+            .bind_all(sqlx::query(&sql))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(ticket_from_row)
+            .collect::<Result<_>>()?;
+
+        Ok((tickets, total))
+    }
+
+    async fn ticket_update(&self, id: u64, ticket_fu: TicketForUpdate) -> Result<Option<Ticket>> {
+        let sql = format!(
+            "UPDATE ticket SET title = COALESCE($1, title) \
+             WHERE id = $2 AND deleted_at IS NULL RETURNING {TICKET_COLS}"
+        );
+        sqlx::query(&sql)
+            .bind(ticket_fu.title)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(ticket_from_row)
+            .transpose()
+    }
+
+    async fn ticket_set_status(
+        &self,
+        id: u64,
+        from: TicketStatus,
+        to: TicketStatus,
+    ) -> Result<Option<Ticket>> {
+        let sql = format!(
+            "UPDATE ticket SET status = $1 \
+             WHERE id = $2 AND status = $3 AND deleted_at IS NULL RETURNING {TICKET_COLS}"
+        );
+        sqlx::query(&sql)
+            .bind(to.as_ref())
+            .bind(id as i64)
+            .bind(from.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(ticket_from_row)
+            .transpose()
+    }
+
+    async fn ticket_soft_delete(&self, id: u64, now: u64) -> Result<Option<Ticket>> {
+        let sql = format!(
+            "UPDATE ticket SET deleted_at = $1 \
+             WHERE id = $2 AND deleted_at IS NULL RETURNING {TICKET_COLS}"
+        );
+        sqlx::query(&sql)
+            .bind(now as i64)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(ticket_from_row)
+            .transpose()
+    }
+
+    async fn ticket_restore(&self, id: u64) -> Result<Option<Ticket>> {
+        let sql = format!(
+            "UPDATE ticket SET deleted_at = NULL \
+             WHERE id = $1 AND deleted_at IS NOT NULL RETURNING {TICKET_COLS}"
+        );
+        sqlx::query(&sql)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(ticket_from_row)
+            .transpose()
+    }
+
+    async fn ticket_purge(&self, deleted_before: u64) -> Result<Vec<Ticket>> {
+        let mut tx = self.pool.begin().await.map_err(store_err)?;
+
+        let sql = format!("DELETE FROM ticket WHERE deleted_at < $1 RETURNING {TICKET_COLS}");
+        let tickets: Vec<Ticket> = sqlx::query(&sql)
+            .bind(deleted_before as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(ticket_from_row)
+            .collect::<Result<_>>()?;
+
+        for ticket in tickets.iter() {
+            sqlx::query("DELETE FROM ticket_share WHERE ticket_id = $1")
+                .bind(ticket.id as i64)
+                .execute(&mut *tx)
+                .await
+                .map_err(store_err)?;
+            sqlx::query("DELETE FROM comment WHERE ticket_id = $1")
+                .bind(ticket.id as i64)
+                .execute(&mut *tx)
+                .await
+                .map_err(store_err)?;
+        }
+
+        tx.commit().await.map_err(store_err)?;
+
+        Ok(tickets)
+    }
+    // endregion: --- Tickets
+
+    // region:    --- Ticket Shares
+    async fn ticket_share_add(&self, ticket_id: u64, user_id: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ticket_share (ticket_id, user_id) VALUES ($1, $2) \
+             ON CONFLICT (ticket_id, user_id) DO NOTHING",
+        )
+        .bind(ticket_id as i64)
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(store_err)?;
+
+        Ok(())
+    }
+
+    async fn ticket_share_remove(&self, ticket_id: u64, user_id: u64) -> Result<()> {
+        sqlx::query("DELETE FROM ticket_share WHERE ticket_id = $1 AND user_id = $2")
+            .bind(ticket_id as i64)
+            .bind(user_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        Ok(())
+    }
+
+    async fn ticket_share_list(&self, ticket_id: u64) -> Result<Vec<u64>> {
+        sqlx::query("SELECT user_id FROM ticket_share WHERE ticket_id = $1 ORDER BY user_id")
+            .bind(ticket_id as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(|row| Ok(row.try_get::<i64, _>("user_id").map_err(store_err)? as u64))
+            .collect()
+    }
+    // endregion: --- Ticket Shares
+
+    // region:    --- Ticket Comments
+    async fn comment_insert(
+        &self,
+        ticket_id: u64,
+        cid: u64,
+        body: String,
+        now: u64,
+    ) -> Result<Comment> {
+        let sql = format!(
+            "INSERT INTO comment (ticket_id, cid, body, ctime, mtime) VALUES ($1, $2, $3, $4, $4) \
+             RETURNING {COMMENT_COLS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(ticket_id as i64)
+            .bind(cid as i64)
+            .bind(body)
+            .bind(now as i64)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        comment_from_row(&row)
+    }
+
+    async fn comment_get(&self, id: u64) -> Result<Option<Comment>> {
+        let sql = format!("SELECT {COMMENT_COLS} FROM comment WHERE id = $1");
+        sqlx::query(&sql)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(comment_from_row)
+            .transpose()
+    }
+
+    async fn comment_list(&self, ticket_id: u64) -> Result<Vec<Comment>> {
+        let sql = format!("SELECT {COMMENT_COLS} FROM comment WHERE ticket_id = $1 ORDER BY id");
+        sqlx::query(&sql)
+            .bind(ticket_id as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(comment_from_row)
+            .collect()
+    }
+
+    async fn comment_update(&self, id: u64, body: String, now: u64) -> Result<Option<Comment>> {
+        let sql = format!(
+            "UPDATE comment SET body = $1, mtime = $2 WHERE id = $3 RETURNING {COMMENT_COLS}"
+        );
+        sqlx::query(&sql)
+            .bind(body)
+            .bind(now as i64)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(comment_from_row)
+            .transpose()
+    }
+
+    async fn comment_delete(&self, id: u64) -> Result<Option<Comment>> {
+        let sql = format!("DELETE FROM comment WHERE id = $1 RETURNING {COMMENT_COLS}");
+        sqlx::query(&sql)
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(comment_from_row)
+            .transpose()
+    }
+    // endregion: --- Ticket Comments
+
+    // region:    --- Audit
+    async fn audit_insert(&self, event: AuditEventForCreate) -> Result<AuditEvent> {
+        let sql = format!(
+            "INSERT INTO audit_event (ticket_id, actor_id, action, diff, ctime) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING {AUDIT_EVENT_COLS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(event.ticket_id as i64)
+            .bind(event.actor_id as i64)
+            .bind(event.action.as_ref())
+            .bind(event.diff.to_string())
+            .bind(event.ctime as i64)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        audit_event_from_row(&row)
+    }
+
+    async fn audit_list(
+        &self,
+        filter: &AuditFilter,
+        list_options: &ListOptions,
+    ) -> Result<(Vec<AuditEvent>, u64)> {
+        // -- Build the where clause.
+        let mut params = Params::default();
+        let mut conds: Vec<String> = Vec::new();
+        if let Some(ticket_id) = filter.ticket_id {
+            let p = params.push(SqlValue::Int(ticket_id as i64));
+            conds.push(format!("ticket_id = {p}"));
+        }
+        if let Some(actor_id) = filter.actor_id {
+            let p = params.push(SqlValue::Int(actor_id as i64));
+            conds.push(format!("actor_id = {p}"));
+        }
+        let where_clause = if conds.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conds.join(" AND "))
+        };
+
+        // -- Count all matching.
+        let sql = format!("SELECT COUNT(*) AS total FROM audit_event {where_clause}");
+        let total = params
+            .bind_all(sqlx::query(&sql))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(store_err)?
+            .try_get::<i64, _>("total")
+            .map_err(store_err)? as u64;
+
+        // -- Fetch the page.
+        let p_limit = params.push(SqlValue::Int(list_options.limit() as i64));
+        let p_offset = params.push(SqlValue::Int(list_options.offset() as i64));
+        let sql = format!(
+            "SELECT {AUDIT_EVENT_COLS} FROM audit_event {where_clause} \
+             ORDER BY id DESC LIMIT {p_limit} OFFSET {p_offset}"
+        );
+        let events = params
+            .bind_all(sqlx::query(&sql))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(audit_event_from_row)
+            .collect::<Result<_>>()?;
+
+        Ok((events, total))
+    }
+    // endregion: --- Audit
+
+    // region:    --- Users
+    async fn user_insert(&self, username: String, pwd_hash: String) -> Result<UserForAuth> {
+        let row =
+            sqlx::query("INSERT INTO app_user (username, pwd_hash) VALUES ($1, $2) RETURNING id")
+                .bind(&username)
+                .bind(&pwd_hash)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|ex| match ex.as_database_error() {
+                    Some(db_ex) if db_ex.is_unique_violation() => Error::UserAlreadyExists {
+                        username: username.clone(),
+                    },
+                    _ => store_err(ex),
+                })?;
+
+        Ok(UserForAuth {
+            id: row.try_get::<i64, _>("id").map_err(store_err)? as u64,
+            username,
+            pwd_hash,
+            role: Role::default(),
+        })
+    }
+
+    async fn user_get(&self, id: u64) -> Result<Option<UserForAuth>> {
+        sqlx::query("SELECT id, username, pwd_hash, role FROM app_user WHERE id = $1")
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(user_from_row)
+            .transpose()
+    }
+
+    async fn user_list(&self) -> Result<Vec<UserForAuth>> {
+        sqlx::query("SELECT id, username, pwd_hash, role FROM app_user ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(store_err)?
+            .iter()
+            .map(user_from_row)
+            .collect()
+    }
+
+    async fn user_first_by_username(&self, username: &str) -> Result<Option<UserForAuth>> {
+        sqlx::query("SELECT id, username, pwd_hash, role FROM app_user WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?
+            .as_ref()
+            .map(user_from_row)
+            .transpose()
+    }
+
+    async fn user_update_pwd(&self, id: u64, pwd_hash: String) -> Result<()> {
+        let result = sqlx::query("UPDATE app_user SET pwd_hash = $1 WHERE id = $2")
+            .bind(pwd_hash)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::UserNotFound { id });
+        }
+
+        Ok(())
+    }
+
+    async fn user_update_role(&self, id: u64, role: Role) -> Result<()> {
+        let result = sqlx::query("UPDATE app_user SET role = $1 WHERE id = $2")
+            .bind(role.as_ref())
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::UserNotFound { id });
+        }
+
+        Ok(())
+    }
+    // endregion: --- Users
+
+    // region:    --- Token Denylist
+    async fn token_deny_add(&self, token_id: &str, until: u64) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(store_err)?;
+
+        sqlx::query("DELETE FROM token_denylist WHERE until <= $1")
+            .bind(now_unix_sec() as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(store_err)?;
+        sqlx::query(
+            "INSERT INTO token_denylist (token_id, until) VALUES ($1, $2) \
+             ON CONFLICT (token_id) DO UPDATE SET until = excluded.until",
+        )
+        .bind(token_id)
+        .bind(until as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(store_err)?;
+
+        tx.commit().await.map_err(store_err)?;
+
+        Ok(())
+    }
+
+    async fn token_deny_contains(&self, token_id: &str) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 AS found FROM token_denylist WHERE token_id = $1")
+            .bind(token_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        Ok(row.is_some())
+    }
+    // endregion: --- Token Denylist
+
+    // region:    --- Api Keys
+    async fn api_key_insert(&self, api_key: ApiKeyForAuth) -> Result<()> {
+        let ApiKeyForAuth {
+            api_key,
+            secret_hash,
+        } = api_key;
+        let permissions = serde_json::to_string(&api_key.permissions)
+            .map_err(|ex| Error::StoreSqlFail(ex.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO api_key (id, user_id, name, permissions, secret_hash, ctime) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(api_key.id)
+        .bind(api_key.user_id as i64)
+        .bind(api_key.name)
+        .bind(permissions)
+        .bind(secret_hash)
+        .bind(api_key.ctime as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(store_err)?;
+
+        Ok(())
+    }
+
+    async fn api_key_get(&self, id: &str) -> Result<Option<ApiKeyForAuth>> {
+        sqlx::query(
+            "SELECT id, user_id, name, permissions, secret_hash, ctime FROM api_key WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(store_err)?
+        .as_ref()
+        .map(api_key_from_row)
+        .transpose()
+    }
+
+    async fn api_key_list(&self, user_id: u64) -> Result<Vec<ApiKeyForAuth>> {
+        sqlx::query(
+            "SELECT id, user_id, name, permissions, secret_hash, ctime FROM api_key \
+             WHERE user_id = $1 ORDER BY ctime, id",
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(store_err)?
+        .iter()
+        .map(api_key_from_row)
+        .collect()
+    }
+
+    async fn api_key_delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM api_key WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(store_err)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+    // endregion: --- Api Keys
+}
+
+// region:    --- Query Params
+enum SqlValue {
+    Int(i64),
+    Text(String),
+}
+
+/// Positional params for dynamically built queries.
+#[derive(Default)]
+struct Params(Vec<SqlValue>);
+
+impl Params {
+    /// Returns the `$N` placeholder for the pushed value.
+    fn push(&mut self, value: SqlValue) -> String {
+        self.0.push(value);
+        format!("${}", self.0.len())
+    }
+
+    fn bind_all<'q>(
+        &'q self,
+        mut query: Query<'q, Any, AnyArguments<'q>>,
+    ) -> Query<'q, Any, AnyArguments<'q>> {
+        for value in &self.0 {
+            query = match value {
+                SqlValue::Int(v) => query.bind(*v),
+                SqlValue::Text(v) => query.bind(v.as_str()),
+            };
+        }
+        query
+    }
+}
+
+/// `%value%` LIKE pattern, with `\`, `%` and `_` escaped.
+fn like_contains(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+// endregion: --- Query Params
+
+// region:    --- Row Mappers
+fn ticket_from_row(row: &AnyRow) -> Result<Ticket> {
+    Ok(Ticket {
+        id: row.try_get::<i64, _>("id").map_err(store_err)? as u64,
+        cid: row.try_get::<i64, _>("cid").map_err(store_err)? as u64,
+        title: row.try_get("title").map_err(store_err)?,
+        status: row
+            .try_get::<String, _>("status")
+            .map_err(store_err)?
+            .parse()?,
+        deleted_at: row
+            .try_get::<Option<i64>, _>("deleted_at")
+            .map_err(store_err)?
+            .map(|t| t as u64),
+    })
+}
+
+fn comment_from_row(row: &AnyRow) -> Result<Comment> {
+    Ok(Comment {
+        id: row.try_get::<i64, _>("id").map_err(store_err)? as u64,
+        ticket_id: row.try_get::<i64, _>("ticket_id").map_err(store_err)? as u64,
+        cid: row.try_get::<i64, _>("cid").map_err(store_err)? as u64,
+        body: row.try_get("body").map_err(store_err)?,
+        ctime: row.try_get::<i64, _>("ctime").map_err(store_err)? as u64,
+        mtime: row.try_get::<i64, _>("mtime").map_err(store_err)? as u64,
+    })
+}
+
+fn audit_event_from_row(row: &AnyRow) -> Result<AuditEvent> {
+    let action: String = row.try_get("action").map_err(store_err)?;
+    let diff: String = row.try_get("diff").map_err(store_err)?;
+
+    Ok(AuditEvent {
+        id: row.try_get::<i64, _>("id").map_err(store_err)? as u64,
+        ticket_id: row.try_get::<i64, _>("ticket_id").map_err(store_err)? as u64,
+        actor_id: row.try_get::<i64, _>("actor_id").map_err(store_err)? as u64,
+        action: serde_json::from_value(Value::String(action))
+            .map_err(|ex| Error::StoreSqlFail(ex.to_string()))?,
+        diff: serde_json::from_str(&diff).map_err(|ex| Error::StoreSqlFail(ex.to_string()))?,
+        ctime: row.try_get::<i64, _>("ctime").map_err(store_err)? as u64,
+    })
+}
+
+fn user_from_row(row: &AnyRow) -> Result<UserForAuth> {
+    Ok(UserForAuth {
+        id: row.try_get::<i64, _>("id").map_err(store_err)? as u64,
+        username: row.try_get("username").map_err(store_err)?,
+        pwd_hash: row.try_get("pwd_hash").map_err(store_err)?,
+        role: row
+            .try_get::<String, _>("role")
+            .map_err(store_err)?
+            .parse()?,
+    })
+}
+
+fn api_key_from_row(row: &AnyRow) -> Result<ApiKeyForAuth> {
+    let permissions: String = row.try_get("permissions").map_err(store_err)?;
+
+    Ok(ApiKeyForAuth {
+        api_key: ApiKey {
+            id: row.try_get("id").map_err(store_err)?,
+            user_id: row.try_get::<i64, _>("user_id").map_err(store_err)? as u64,
+            name: row.try_get("name").map_err(store_err)?,
+            permissions: serde_json::from_str(&permissions)
+                .map_err(|ex| Error::StoreSqlFail(ex.to_string()))?,
+            ctime: row.try_get::<i64, _>("ctime").map_err(store_err)? as u64,
+        },
+        secret_hash: row.try_get("secret_hash").map_err(store_err)?,
+    })
+}
+
+fn store_err(ex: sqlx::Error) -> Error {
+    Error::StoreSqlFail(ex.to_string())
+}
+// endregion: --- Row Mappers
@@ -0,0 +1,218 @@
+//! User Backend Model Controller
+//! (users live alongside the tickets in the ModelController store)
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypt::pwd::{hash_pwd, validate_pwd},
+    ctx::Ctx,
+    Error, Result,
+};
+
+use super::ModelController;
+
+/// Argon2id hash (default params, as `hash_pwd`) validated for the unknown usernames.
+const DUMMY_PWD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$U6quJHdLHF1OQrSHDaWqVg$G1Bz0OJPGem7TYXPXMs0VfrZBiEhxrZxPNJzu0KXTm4";
+
+// region:    --- Roles and Permissions
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum_macros::AsRefStr,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Role {
+    Admin,
+    #[default]
+    Member,
+    Viewer,
+}
+
+impl FromStr for Role {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "admin" => Ok(Self::Admin),
+            "member" => Ok(Self::Member),
+            "viewer" => Ok(Self::Viewer),
+            _ => Err(Error::UserRoleInvalid(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    TicketRead,
+    TicketCreate,
+    /// Update, share and comment.
+    TicketUpdate,
+    TicketDelete,
+    /// Read/write any ticket, not only the own or shared ones.
+    TicketAdmin,
+    /// List users and change their roles.
+    UserManage,
+    /// Global ticket audit feed.
+    AuditRead,
+    /// Create, list and revoke the own api keys.
+    /// Never granted to an api key (key management needs a cookie session).
+    ApiKeyManage,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            Self::Admin => &[
+                TicketRead,
+                TicketCreate,
+                TicketUpdate,
+                TicketDelete,
+                TicketAdmin,
+                UserManage,
+                AuditRead,
+                ApiKeyManage,
+            ],
+            Self::Member => &[
+                TicketRead,
+                TicketCreate,
+                TicketUpdate,
+                TicketDelete,
+                ApiKeyManage,
+            ],
+            Self::Viewer => &[TicketRead, ApiKeyManage],
+        }
+    }
+}
+// endregion: --- Roles and Permissions
+
+// region:    --- User Types
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct User {
+    pub id: u64,
+    pub username: String,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+pub struct UserForCreate {
+    pub username: String,
+    pub pwd_clear: String,
+}
+
+/// Stored user record, never sent to the client.
+#[derive(Clone)]
+pub struct UserForAuth {
+    pub id: u64,
+    pub username: String,
+    pub pwd_hash: String,
+    pub role: Role,
+}
+
+impl From<&UserForAuth> for User {
+    fn from(user: &UserForAuth) -> Self {
+        Self {
+            id: user.id,
+            username: user.username.clone(),
+            role: user.role,
+        }
+    }
+}
+// endregion: --- User Types
+
+// region:    --- User Bmc
+pub struct UserBmc;
+
+impl UserBmc {
+    pub async fn create(mc: &ModelController, user_fc: UserForCreate) -> Result<User> {
+        let UserForCreate {
+            username,
+            pwd_clear,
+        } = user_fc;
+
+        if username.trim().is_empty() || pwd_clear.is_empty() {
+            return Err(Error::UserInvalidParams);
+        }
+
+        let pwd_hash = hash_pwd(pwd_clear).await?;
+        let user = mc.store.user_insert(username, pwd_hash).await?;
+
+        Ok(User::from(&user))
+    }
+
+    pub async fn first_by_username(
+        mc: &ModelController,
+        username: &str,
+    ) -> Result<Option<UserForAuth>> {
+        mc.store.user_first_by_username(username).await
+    }
+
+    pub async fn get(mc: &ModelController, id: u64) -> Result<UserForAuth> {
+        mc.store
+            .user_get(id)
+            .await?
+            .ok_or(Error::UserNotFound { id })
+    }
+
+    /// Returns the user when `username` / `pwd_clear` match a stored user.
+    /// Any mismatch (unknown user or wrong password) is a `LoginFail`.
+    pub async fn login(
+        mc: &ModelController,
+        username: &str,
+        pwd_clear: String,
+    ) -> Result<UserForAuth> {
+        let Some(user) = Self::first_by_username(mc, username).await? else {
+            // Same (slow) hash validation as for a known user, so the response time
+            // does not tell whether the username exists.
+            let _ = validate_pwd(pwd_clear, DUMMY_PWD_HASH.to_string()).await;
+            return Err(Error::LoginFail);
+        };
+
+        validate_pwd(pwd_clear, user.pwd_hash.clone())
+            .await
+            .map_err(|_| Error::LoginFail)?;
+
+        Ok(user)
+    }
+
+    pub async fn update_pwd(
+        mc: &ModelController,
+        ctx: &Ctx,
+        pwd_old: String,
+        pwd_new: String,
+    ) -> Result<()> {
+        if pwd_new.is_empty() {
+            return Err(Error::UserInvalidParams);
+        }
+
+        let user = Self::get(mc, ctx.user_id()).await?;
+        validate_pwd(pwd_old, user.pwd_hash).await?;
+
+        let pwd_hash = hash_pwd(pwd_new).await?;
+        mc.store.user_update_pwd(user.id, pwd_hash).await
+    }
+
+    pub async fn list(mc: &ModelController) -> Result<Vec<User>> {
+        let users = mc.store.user_list().await?;
+
+        Ok(users.iter().map(User::from).collect())
+    }
+
+    pub async fn update_role(mc: &ModelController, id: u64, role: Role) -> Result<()> {
+        mc.store.user_update_role(id, role).await
+    }
+}
+// endregion: --- User Bmc
@@ -0,0 +1,66 @@
+//! Tracing (diagnostics) subscriber
+//!
+//! Each request runs in a `request` span (see `mw_req_id`) with the `req_id`, `method`
+//! and `uri`, plus the `route` and `user_id` once resolved. The handlers, middlewares and
+//! extractors emit `debug` events in it.
+//!
+//! NOTE: Not the request log (`log` module), which is the one line per request audit trail.
+
+use std::str::FromStr;
+
+use tracing_subscriber::EnvFilter;
+
+use crate::{config::Config, Error, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Human readable, multi-line.
+    Pretty,
+    /// One json object per line (with the current span fields).
+    Json,
+}
+
+impl FromStr for TraceFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> core::result::Result<Self, ()> {
+        match s {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Install the global subscriber (once, at startup).
+pub fn init_tracing(config: &Config) -> Result<()> {
+    let filter = env_filter(config)?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false);
+
+    let res = match config.trace_format {
+        TraceFormat::Pretty => builder.pretty().try_init(),
+        TraceFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init(),
+    };
+
+    res.map_err(|ex| Error::TraceInitFail(ex.to_string()))
+}
+
+/// `RUST_LOG` when set, otherwise the config filter.
+fn env_filter(config: &Config) -> Result<EnvFilter> {
+    match std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|v| !v.is_empty())
+    {
+        Some(directives) => {
+            EnvFilter::try_new(directives).map_err(|_| Error::ConfigWrongFormat("RUST_LOG"))
+        }
+        None => EnvFilter::try_new(&config.trace_filter)
+            .map_err(|_| Error::ConfigWrongFormat("SERVICE_TRACE_FILTER")),
+    }
+}
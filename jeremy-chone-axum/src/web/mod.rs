@@ -1,5 +1,79 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use tower_cookies::{Cookie, Cookies};
+
+use crate::{
+    config::Config,
+    crypt::token::{generate_token, refresh_token, Token},
+    model::{user::UserForAuth, ModelController},
+    Result,
+};
+
+pub mod mw_auth;
+pub mod mw_metrics;
+pub mod mw_rate_limit;
+pub mod mw_req_id;
+pub mod routes_admin;
+pub mod routes_api_keys;
+pub mod routes_health;
 pub mod routes_login;
+pub mod routes_openapi;
+pub mod routes_rpc;
+pub mod routes_static;
+pub mod routes_ticket_events;
 pub mod routes_tickets;
-pub mod mw_auth;
 
-pub const AUTH_TOKEN: &str = "auth-token";
\ No newline at end of file
+/// Default name of the auth cookie (see `Config::auth_cookie`).
+pub const AUTH_TOKEN: &str = "auth-token";
+
+/// State of the routes and middlewares needing the config.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub mc: ModelController,
+    pub config: Arc<Config>,
+}
+
+/// Generate a new signed token (new session) for `user` and set it as the auth cookie.
+fn set_token_cookie(config: &Config, cookies: &Cookies, user: &UserForAuth) -> Result<()> {
+    let key = session_key(config, user);
+    let token = generate_token(user.id, config.token_duration_sec, &key)?;
+    add_token_cookie(config, cookies, &token);
+
+    Ok(())
+}
+
+/// Re-issue the (validated) `token` of `user` with a new expiration (sliding session).
+fn refresh_token_cookie(
+    config: &Config,
+    cookies: &Cookies,
+    token: &Token,
+    user: &UserForAuth,
+) -> Result<()> {
+    let key = session_key(config, user);
+    let token = refresh_token(token, config.token_duration_sec, &key)?;
+    add_token_cookie(config, cookies, &token);
+
+    Ok(())
+}
+
+/// Signing key of the session tokens of `user`.
+/// The password hash is part of it, so a password change ends all the sessions of the user.
+fn session_key(config: &Config, user: &UserForAuth) -> Vec<u8> {
+    [config.token_key.as_slice(), user.pwd_hash.as_bytes()].concat()
+}
+
+fn remove_token_cookie(config: &Config, cookies: &Cookies) {
+    let mut cookie = Cookie::from(config.auth_cookie.clone());
+    cookie.set_path("/");
+
+    cookies.remove(cookie);
+}
+
+fn add_token_cookie(config: &Config, cookies: &Cookies, token: &Token) {
+    let mut cookie = Cookie::new(config.auth_cookie.clone(), token.to_string());
+    cookie.set_http_only(true);
+    cookie.set_path("/");
+
+    cookies.add(cookie);
+}
@@ -4,19 +4,35 @@ use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::{FromRequestParts, Request, State},
-    http::request::Parts,
+    http::{header, request::Parts},
     middleware::Next,
     response::Response,
 };
-use lazy_regex::regex_captures;
-use tower_cookies::{Cookie, Cookies};
-
-use crate::{ctx::Ctx, model::ModelController, Error, Result};
+use std::sync::Arc;
+
+use tower_cookies::Cookies;
+use tracing::{debug, Span};
+
+use crate::{
+    config::Config,
+    crypt::{
+        api_key::ApiKeyClear,
+        token::{validate_token, Token},
+    },
+    ctx::Ctx,
+    model::{
+        api_key::ApiKeyBmc,
+        session::SessionBmc,
+        user::{Permission, UserBmc},
+        ModelController,
+    },
+    Error, Result,
+};
 
-use super::AUTH_TOKEN;
+use super::{mw_req_id::ReqId, refresh_token_cookie, remove_token_cookie, session_key};
 
 pub async fn mw_require_auth(ctx: Result<Ctx>, req: Request<Body>, next: Next) -> Result<Response> {
-    println!("->> {:<12} - mw_require_auth - {ctx:?}", "MIDDLEWARE");
+    debug!("{:<12} - mw_require_auth - {ctx:?}", "MIDDLEWARE");
 
     ctx?;
     // let ctx = ctx?;
@@ -25,31 +41,59 @@ pub async fn mw_require_auth(ctx: Result<Ctx>, req: Request<Body>, next: Next) -
     Ok(next.run(req).await)
 }
 
+/// Route layer requiring a given permission (implies `mw_require_auth`).
+///
+/// e.g., `.route_layer(middleware::from_fn_with_state(Permission::UserManage, mw_require_permission))`
+pub async fn mw_require_permission(
+    State(permission): State<Permission>,
+    ctx: Result<Ctx>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    debug!(
+        "{:<12} - mw_require_permission - {permission:?}",
+        "MIDDLEWARE"
+    );
+
+    ctx?.require(permission)?;
+
+    Ok(next.run(req).await)
+}
+
 pub async fn mw_ctx_resolver(
-    _mc: State<ModelController>,
+    State(mc): State<ModelController>,
+    State(config): State<Arc<Config>>,
     cookies: Cookies,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response> {
-    println!("->> {:<12} - mw_ctx_resolver", "MIDDLEWARE");
-
-    let auth_token = cookies.get(AUTH_TOKEN).map(|c| c.value().to_string());
-
-    // Compute Result<Ctx>
-    let result_ctx = match auth_token
-        .ok_or(Error::AuthFailNoAuthTokenCookie)
-        .and_then(parse_token)
-    {
-        Ok((user_id, _exp, _sign)) => {
-            // TODO: Token components validations.
-            Ok(Ctx::new(user_id))
+    debug!("{:<12} - mw_ctx_resolver", "MIDDLEWARE");
+
+    let req_id = req
+        .extensions()
+        .get::<ReqId>()
+        .cloned()
+        .ok_or(Error::ReqIdNotInRequestExt)?;
+
+    // Compute Result<Ctx>, the api key (machine clients) takes precedence over the cookie.
+    let result_ctx = match bearer_value(&req) {
+        Some(api_key) => resolve_ctx_api_key(&mc, api_key, req_id).await,
+        None => {
+            // Also re-issues the auth cookie on success.
+            let result_ctx = resolve_ctx(&mc, &config, &cookies, req_id).await;
+
+            // Remove the cookie only if its token is rejected
+            // (e.g., not when the store is unreachable, the session is still valid).
+            if result_ctx.as_ref().is_err_and(|ex| ex.is_auth_token_fail()) {
+                remove_token_cookie(&config, &cookies);
+            }
+
+            result_ctx
         }
-        Err(e) => Err(e),
     };
 
-    // Remove the cookie if something went wrong other than NoAuthTokenCookie.
-    if result_ctx.is_err() && !matches!(result_ctx, Err(Error::AuthFailNoAuthTokenCookie)) {
-        cookies.remove(Cookie::from(AUTH_TOKEN))
+    if let Ok(ctx) = &result_ctx {
+        Span::current().record("user_id", ctx.user_id());
     }
 
     // Store the ctx_result in the request extension.
@@ -58,12 +102,69 @@ pub async fn mw_ctx_resolver(
     Ok(next.run(req).await)
 }
 
+async fn resolve_ctx(
+    mc: &ModelController,
+    config: &Config,
+    cookies: &Cookies,
+    req_id: ReqId,
+) -> Result<Ctx> {
+    let token = cookies
+        .get(&config.auth_cookie)
+        .ok_or(Error::AuthFailNoAuthTokenCookie)?
+        .value()
+        .parse::<Token>()?;
+
+    // Resolve the user role, permissions and token key (the user might have been
+    // removed since login).
+    let user = UserBmc::get(mc, token.user_id)
+        .await
+        .map_err(|ex| match ex {
+            Error::UserNotFound { .. } => Error::AuthFailUserNotFound,
+            ex => ex,
+        })?;
+
+    // Validate signature and expiration.
+    validate_token(&token, &session_key(config, &user))?;
+
+    // Server-side revocation (logoff).
+    if SessionBmc::is_revoked(mc, &token.token_id).await? {
+        return Err(Error::AuthFailTokenRevoked);
+    }
+
+    // Sliding session - new expiration on each authenticated request.
+    refresh_token_cookie(config, cookies, &token, &user)?;
+
+    Ok(Ctx::new(
+        req_id,
+        user.id,
+        user.role,
+        user.role.permissions().iter().copied(),
+    ))
+}
+
+/// `Authorization: Bearer <api-key>`
+async fn resolve_ctx_api_key(mc: &ModelController, api_key: &str, req_id: ReqId) -> Result<Ctx> {
+    let key_clear = api_key.parse::<ApiKeyClear>()?;
+    let (user, permissions) = ApiKeyBmc::auth(mc, &key_clear).await?;
+
+    Ok(Ctx::new(req_id, user.id, user.role, permissions))
+}
+
+fn bearer_value(req: &Request<Body>) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
 // region:    --- Ctx Extractor
 #[async_trait]
 impl<S: Send + Sync> FromRequestParts<S> for Ctx {
     type Rejection = Error;
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
-        println!("->> {:<12} - Ctx", "EXTRACTOR");
+        debug!("{:<12} - Ctx", "EXTRACTOR");
 
         // region:    --- optimazed
 
@@ -89,20 +190,3 @@ impl<S: Send + Sync> FromRequestParts<S> for Ctx {
     }
 }
 // // endregion: --- Ctx Extractor
-
-/// Parse a token of format `user-[user-id].[expiration].[signature]
-/// Returns (user_id, expiration, signature)
-fn parse_token(token: String) -> Result<(u64, String, String)> {
-    let (_whole, user_id, exp, sign) = regex_captures!(
-        r#"^user-(\d+)\.(.+)\.(.+)"#, // a literal regex
-        &token
-    )
-    .ok_or(Error::AuthFailTokenWrongFormat)?;
-
-    // Parse token.
-    let user_id = user_id
-        .parse::<u64>()
-        .map_err(|_| Error::AuthFailTokenWrongFormat)?;
-
-    Ok((user_id, exp.to_string(), sign.to_string()))
-}
@@ -0,0 +1,292 @@
+//! Request metrics (in-memory, per server instance), in Prometheus text format
+//! - `http_requests_total{method, route, status}`
+//! - `http_request_duration_seconds{method, route}` - histogram, until the response head.
+//! - `http_request_errors_total{method, route, error}` - by `ClientError` type.
+//! - `http_requests_in_flight` - gauge (also for the shutdown drain).
+//!
+//! NOTE: `route` is the matched route (e.g., `/api/tickets/:id`) and `method` is `other` for
+//!       the non-standard methods, to keep the label cardinality bounded.
+//!       The static files fallback is not recorded.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use tracing::{debug, Span};
+
+use crate::error::ClientError;
+
+/// Latency histogram upper bounds (sec).
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+// region:    --- Metrics
+#[derive(Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsInner>>,
+    in_flight: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+struct MetricsInner {
+    /// (method, route, status) -> count
+    requests: BTreeMap<(String, String, u16), u64>,
+    /// (method, route) -> histogram
+    durations: BTreeMap<(String, String), Histogram>,
+    /// (method, route, client error) -> count
+    errors: BTreeMap<(String, String, String), u64>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Per bucket (not cumulative, summed on render).
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Metrics {
+    /// Requests being served (handler not returned yet).
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    fn record(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        duration_sec: f64,
+        error: Option<&str>,
+    ) {
+        // Metrics never fail the request.
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let (method, route) = (method.to_string(), route.to_string());
+
+        *inner
+            .requests
+            .entry((method.clone(), route.clone(), status))
+            .or_default() += 1;
+
+        let histogram = inner
+            .durations
+            .entry((method.clone(), route.clone()))
+            .or_default();
+        if let Some(idx) = DURATION_BUCKETS.iter().position(|le| duration_sec <= *le) {
+            histogram.buckets[idx] += 1;
+        }
+        histogram.sum += duration_sec;
+        histogram.count += 1;
+
+        if let Some(error) = error {
+            *inner
+                .errors
+                .entry((method, route, error.to_string()))
+                .or_default() += 1;
+        }
+    }
+
+    /// Prometheus text exposition format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let Ok(inner) = self.inner.lock() else {
+            return String::new();
+        };
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_in_flight Requests being served.\n");
+        out.push_str("# TYPE http_requests_in_flight gauge\n");
+        let _ = writeln!(out, "http_requests_in_flight {}", self.in_flight());
+
+        out.push_str("# HELP http_requests_total Number of requests.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, route, status), count) in inner.requests.iter() {
+            let labels = labels(&[
+                ("method", method),
+                ("route", route),
+                ("status", &status.to_string()),
+            ]);
+            let _ = writeln!(out, "http_requests_total{{{labels}}} {count}");
+        }
+
+        out.push_str("# HELP http_request_duration_seconds Request latency.\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, route), histogram) in inner.durations.iter() {
+            let labels = labels(&[("method", method), ("route", route)]);
+            let mut cumulative = 0;
+            for (le, count) in DURATION_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "http_request_duration_seconds_bucket{{{labels},le=\"{le}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_count{{{labels}}} {}",
+                histogram.count
+            );
+        }
+
+        out.push_str(
+            "# HELP http_request_errors_total Number of error responses by client error type.\n",
+        );
+        out.push_str("# TYPE http_request_errors_total counter\n");
+        for ((method, route, error), count) in inner.errors.iter() {
+            let labels = labels(&[("method", method), ("route", route), ("error", error)]);
+            let _ = writeln!(out, "http_request_errors_total{{{labels}}} {count}");
+        }
+
+        out
+    }
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Counts the request in flight until dropped (also when the request is cancelled).
+struct InFlightGuard(Arc<AtomicU64>);
+
+impl InFlightGuard {
+    fn new(in_flight: &Arc<AtomicU64>) -> Self {
+        in_flight.fetch_add(1, Ordering::Relaxed);
+        Self(in_flight.clone())
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+// endregion: --- Metrics
+
+/// Just outside `main_response_mapper`, so the status is the client one
+/// (e.g., `204` for a JSON-RPC notification), with its `ClientError` in the extensions.
+pub async fn mw_metrics(
+    State(metrics): State<Metrics>,
+    matched_path: Option<MatchedPath>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    debug!("{:<12} - mw_metrics", "MIDDLEWARE");
+
+    let method = method_label(req.method());
+    let route = matched_path
+        .as_ref()
+        .map_or("unmatched", |p| p.as_str())
+        .to_string();
+    Span::current().record("route", route.as_str());
+    let start = Instant::now();
+
+    let in_flight = InFlightGuard::new(&metrics.in_flight);
+    let res = next.run(req).await;
+    drop(in_flight);
+
+    metrics.record(
+        method,
+        &route,
+        res.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+        res.extensions().get::<ClientError>().map(|e| e.as_ref()),
+    );
+
+    res
+}
+
+/// The standard methods, `other` for the rest (e.g., a WebDAV `PROPFIND`).
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "other",
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::default();
+        metrics.record("GET", "/api/tickets/:id", 200, 0.02, None);
+        metrics.record(
+            "GET",
+            "/api/tickets/:id",
+            404,
+            0.3,
+            Some("ENTITY_NOT_FOUND"),
+        );
+        let in_flight = InFlightGuard::new(&metrics.in_flight);
+
+        let out = metrics.render();
+        assert!(out.contains("http_requests_in_flight 1\n"));
+        drop(in_flight);
+        assert_eq!(metrics.in_flight(), 0);
+        assert!(out.contains(
+            r#"http_requests_total{method="GET",route="/api/tickets/:id",status="404"} 1"#
+        ));
+        assert!(out.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="/api/tickets/:id",le="0.025"} 1"#
+        ));
+        assert!(out.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="/api/tickets/:id",le="+Inf"} 2"#
+        ));
+        assert!(out.contains(
+            r#"http_request_errors_total{method="GET",route="/api/tickets/:id",error="ENTITY_NOT_FOUND"} 1"#
+        ));
+    }
+
+    #[test]
+    fn test_metrics_method_label() {
+        assert_eq!(method_label(&Method::PATCH), "PATCH");
+        let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+        assert_eq!(method_label(&propfind), "other");
+    }
+}
+// endregion: --- Tests
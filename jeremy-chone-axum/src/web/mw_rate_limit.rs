@@ -0,0 +1,401 @@
+//! Rate limiting and login lockout (in-memory, per server instance)
+//! - `RateLimiter`  - token bucket per key (client ip, username).
+//! - `LoginLockout` - temporary account lock after N failed logins within a window.
+//!
+//! NOTE: The client ip is the peer address, `X-Forwarded-For` is not trusted.
+//! NOTE: The keys are bounded, a new one is refused (429) when full of non-idle entries.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tracing::debug;
+
+use crate::{Error, Result};
+
+/// Above this number of entries (buckets, accounts), the idle ones are dropped.
+const ENTRIES_PRUNE_LEN: usize = 10_000;
+/// Min time between two prunes (a full scan under the lock).
+const ENTRIES_PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+/// No new key above this number of entries (until the idle ones are dropped).
+const ENTRIES_MAX_LEN: usize = 100_000;
+
+// region:    --- Entries
+/// The entries per key (buckets, accounts), bounded.
+struct Entries<T> {
+    map: HashMap<String, T>,
+    last_prune: Option<Instant>,
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            last_prune: None,
+        }
+    }
+}
+
+impl<T> Entries<T> {
+    /// The entry of `key`, inserted with `new` when missing.
+    /// Returns `None` when full (no room for a new key, even after the prune).
+    fn get_or_insert(
+        &mut self,
+        key: &str,
+        now: Instant,
+        is_idle: impl Fn(&T) -> bool,
+        new: impl FnOnce() -> T,
+    ) -> Option<&mut T> {
+        if !self.map.contains_key(key) {
+            let prune_due = self.last_prune.is_none_or(|last_prune| {
+                now.saturating_duration_since(last_prune) >= ENTRIES_PRUNE_INTERVAL
+            });
+            if self.map.len() >= ENTRIES_PRUNE_LEN && prune_due {
+                self.map.retain(|_, entry| !is_idle(entry));
+                self.last_prune = Some(now);
+            }
+            if self.map.len() >= ENTRIES_MAX_LEN {
+                return None;
+            }
+        }
+
+        Some(self.map.entry(key.to_string()).or_insert_with(new))
+    }
+}
+// endregion: --- Entries
+
+// region:    --- Rate Limiter
+/// Token bucket per key, `capacity` tokens refilled over one `period`.
+#[derive(Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<Entries<Bucket>>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            capacity,
+            refill_per_sec: capacity / period.as_secs_f64(),
+            buckets: Arc::default(),
+        }
+    }
+
+    pub fn per_min(capacity: u32) -> Self {
+        Self::new(capacity, Duration::from_secs(60))
+    }
+
+    /// Takes one token for `key`, fails with `Error::RateLimited` when empty.
+    pub fn check(&self, key: &str) -> Result<()> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<()> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .map_err(|_| Error::RateLimitLockPoisoned)?;
+
+        // Full buckets (idle) are dropped, a new key gets a full one.
+        let bucket = buckets
+            .get_or_insert(
+                key,
+                now,
+                |b| self.refilled(b, now) >= self.capacity,
+                || Bucket {
+                    tokens: self.capacity,
+                    last: now,
+                },
+            )
+            .ok_or(Error::RateLimited {
+                retry_after_sec: ENTRIES_PRUNE_INTERVAL.as_secs(),
+            })?;
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.last = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let retry_after_sec = ((1.0 - bucket.tokens) / self.refill_per_sec).ceil() as u64;
+            Err(Error::RateLimited {
+                retry_after_sec: retry_after_sec.max(1),
+            })
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+/// Route layer limiting by client ip.
+///
+/// e.g., `.route_layer(middleware::from_fn_with_state(RateLimiter::per_min(10), mw_rate_limit))`
+///
+/// NOTE: Requires `into_make_service_with_connect_info::<SocketAddr>()`.
+pub async fn mw_rate_limit(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    debug!("{:<12} - mw_rate_limit - {}", "MIDDLEWARE", addr.ip());
+
+    limiter.check(&addr.ip().to_string())?;
+
+    Ok(next.run(req).await)
+}
+// endregion: --- Rate Limiter
+
+// region:    --- Login Lockout
+/// Failed logins per username within `window` (from the first one), reset on success.
+#[derive(Clone)]
+pub struct LoginLockout {
+    max_failures: u32,
+    window: Duration,
+    lock_duration: Duration,
+    accounts: Arc<Mutex<Entries<Failures>>>,
+}
+
+struct Failures {
+    count: u32,
+    first_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl LoginLockout {
+    pub fn new(max_failures: u32, window: Duration, lock_duration: Duration) -> Self {
+        Self {
+            max_failures: max_failures.max(1),
+            window,
+            lock_duration,
+            accounts: Arc::default(),
+        }
+    }
+
+    /// Fails with `Error::LoginLocked` while the account is locked
+    /// (checked before the password, so even a valid one is refused).
+    pub fn check(&self, username: &str) -> Result<()> {
+        self.check_at(username, Instant::now())
+    }
+
+    fn check_at(&self, username: &str, now: Instant) -> Result<()> {
+        let mut accounts = self
+            .accounts
+            .lock()
+            .map_err(|_| Error::RateLimitLockPoisoned)?;
+
+        match accounts.map.get(username).and_then(|f| f.locked_until) {
+            Some(locked_until) if locked_until > now => Err(Error::LoginLocked {
+                retry_after_sec: (locked_until - now).as_secs().max(1),
+            }),
+            // Lock over, a new series of attempts.
+            Some(_) => {
+                accounts.map.remove(username);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_failure(&self, username: &str) -> Result<()> {
+        self.record_failure_at(username, Instant::now())
+    }
+
+    fn record_failure_at(&self, username: &str, now: Instant) -> Result<()> {
+        let mut accounts = self
+            .accounts
+            .lock()
+            .map_err(|_| Error::RateLimitLockPoisoned)?;
+
+        let failures = accounts
+            .get_or_insert(
+                username,
+                now,
+                |f| self.is_expired(f, now),
+                || Failures {
+                    count: 0,
+                    first_failure: now,
+                    locked_until: None,
+                },
+            )
+            .ok_or(Error::LoginLocked {
+                retry_after_sec: ENTRIES_PRUNE_INTERVAL.as_secs(),
+            })?;
+        // Window over, a new series of attempts.
+        if self.is_expired(failures, now) {
+            *failures = Failures {
+                count: 0,
+                first_failure: now,
+                locked_until: None,
+            };
+        }
+        failures.count += 1;
+        if failures.count >= self.max_failures {
+            failures.locked_until = Some(now + self.lock_duration);
+        }
+
+        Ok(())
+    }
+
+    pub fn record_success(&self, username: &str) -> Result<()> {
+        let mut accounts = self
+            .accounts
+            .lock()
+            .map_err(|_| Error::RateLimitLockPoisoned)?;
+        accounts.map.remove(username);
+
+        Ok(())
+    }
+
+    /// Past its lock, or its window when not locked.
+    fn is_expired(&self, failures: &Failures, now: Instant) -> bool {
+        match failures.locked_until {
+            Some(locked_until) => locked_until <= now,
+            None => now.saturating_duration_since(failures.first_failure) >= self.window,
+        }
+    }
+}
+// endregion: --- Login Lockout
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_bucket_refill() -> Result<()> {
+        // 2 requests per 10 sec (one token every 5 sec).
+        let limiter = RateLimiter::new(2, Duration::from_secs(10));
+        let t0 = Instant::now();
+
+        limiter.check_at("ip-a", t0)?;
+        limiter.check_at("ip-a", t0)?;
+        assert!(matches!(
+            limiter.check_at("ip-a", t0),
+            Err(Error::RateLimited { retry_after_sec: 5 })
+        ));
+        // Other keys have their own bucket.
+        limiter.check_at("ip-b", t0)?;
+
+        limiter.check_at("ip-a", t0 + Duration::from_secs(5))?;
+        assert!(limiter
+            .check_at("ip-a", t0 + Duration::from_secs(6))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_login_lockout() -> Result<()> {
+        let lockout = LoginLockout::new(2, Duration::from_secs(60), Duration::from_secs(60));
+
+        lockout.record_failure("demo1")?;
+        lockout.check("demo1")?;
+        lockout.record_success("demo1")?;
+
+        lockout.record_failure("demo1")?;
+        lockout.record_failure("demo1")?;
+        assert!(matches!(
+            lockout.check("demo1"),
+            Err(Error::LoginLocked { .. })
+        ));
+        lockout.check("demo2")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_login_lockout_window() -> Result<()> {
+        // 2 failures within 10 sec, locked 60 sec.
+        let lockout = LoginLockout::new(2, Duration::from_secs(10), Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        // -- Spread over more than the window, not locked.
+        lockout.record_failure_at("demo1", t0)?;
+        lockout.record_failure_at("demo1", t0 + Duration::from_secs(10))?;
+        lockout.check_at("demo1", t0 + Duration::from_secs(10))?;
+
+        // -- Within the window (of the first failure of the new series).
+        let t1 = t0 + Duration::from_secs(19);
+        lockout.record_failure_at("demo1", t1)?;
+        assert!(matches!(
+            lockout.check_at("demo1", t1),
+            Err(Error::LoginLocked {
+                retry_after_sec: 60
+            })
+        ));
+        lockout.check_at("demo1", t1 + Duration::from_secs(60))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_login_lockout_prune() -> Result<()> {
+        let lockout = LoginLockout::new(5, Duration::from_secs(10), Duration::from_secs(60));
+        let len = || lockout.accounts.lock().unwrap().map.len();
+        let t0 = Instant::now();
+
+        for i in 0..ENTRIES_PRUNE_LEN {
+            lockout.record_failure_at(&format!("user-{i}"), t0)?;
+        }
+        // Still in their window, kept.
+        let t1 = t0 + Duration::from_secs(9);
+        lockout.record_failure_at("other", t1)?;
+        assert_eq!(len(), ENTRIES_PRUNE_LEN + 1);
+
+        // Past their window, but pruned less than an interval ago.
+        lockout.record_failure_at("next", t0 + Duration::from_secs(10))?;
+        assert_eq!(len(), ENTRIES_PRUNE_LEN + 2);
+
+        // Dropped (and "other" is past its window too).
+        lockout.record_failure_at("last", t1 + ENTRIES_PRUNE_INTERVAL)?;
+        assert_eq!(len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limiter_max_len() -> Result<()> {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        for i in 0..ENTRIES_MAX_LEN {
+            limiter.check_at(&format!("ip-{i}"), t0)?;
+        }
+
+        // Full (none idle), no new key, the known ones still count.
+        assert!(matches!(
+            limiter.check_at("ip-new", t0),
+            Err(Error::RateLimited {
+                retry_after_sec: 10
+            })
+        ));
+        limiter.check_at("ip-0", t0)?;
+
+        // Refilled (idle) after the period, dropped for the new key.
+        limiter.check_at("ip-new", t0 + Duration::from_secs(60))?;
+        assert_eq!(limiter.buckets.lock().unwrap().map.len(), 1);
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,131 @@
+//! Request id
+//!
+//! Stamped at the very start of the pipeline (outermost layer), taken from the
+//! incoming `X-Request-Id` header when it is well formed, otherwise a new uuid.
+//! It is stored in the request extensions (and in the `Ctx`), echoed in the
+//! `X-Request-Id` response header, and used in the error bodies and log lines.
+//!
+//! The rest of the pipeline runs in the `request` tracing span (see `trace`).
+
+use std::fmt;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{debug, field, info_span, Instrument};
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Max length of an accepted incoming request id.
+const REQ_ID_MAX_LEN: usize = 128;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReqId(String);
+
+impl ReqId {
+    /// New random (uuid v4) request id.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Accepts `[A-Za-z0-9._:-]` only (max 128 chars), as the id ends up in
+    /// response headers and log lines.
+    pub fn parse(value: &str) -> Option<Self> {
+        let valid = !value.is_empty()
+            && value.len() <= REQ_ID_MAX_LEN
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b':' | b'-'));
+
+        valid.then(|| Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for ReqId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ReqId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub async fn mw_req_id(mut req: Request<Body>, next: Next) -> Response {
+    let req_id = req
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ReqId::parse)
+        .unwrap_or_default();
+
+    // `route` and `user_id` recorded once resolved (`mw_metrics`, `mw_ctx_resolver`).
+    let span = info_span!(
+        "request",
+        req_id = %req_id,
+        method = %req.method(),
+        uri = %req.uri(),
+        route = field::Empty,
+        user_id = field::Empty,
+    );
+    span.in_scope(|| debug!("{:<12} - mw_req_id", "MIDDLEWARE"));
+
+    req.extensions_mut().insert(req_id.clone());
+
+    let mut res = next.run(req).instrument(span).await;
+
+    // Only valid header chars per `ReqId::parse`.
+    if let Ok(value) = HeaderValue::from_str(req_id.as_str()) {
+        res.headers_mut().insert(X_REQUEST_ID, value);
+    }
+
+    res
+}
+
+// region:    --- ReqId Extractor
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ReqId {
+    type Rejection = Error;
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        parts
+            .extensions
+            .get::<ReqId>()
+            .cloned()
+            .ok_or(Error::ReqIdNotInRequestExt)
+    }
+}
+// endregion: --- ReqId Extractor
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_req_id_parse() {
+        assert_eq!(
+            ReqId::parse("abc-123_x.y:z").unwrap().as_str(),
+            "abc-123_x.y:z"
+        );
+
+        assert!(ReqId::parse("").is_none());
+        assert!(ReqId::parse("has space").is_none());
+        assert!(ReqId::parse("line\nbreak").is_none());
+        assert!(ReqId::parse(&"a".repeat(REQ_ID_MAX_LEN + 1)).is_none());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,87 @@
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, patch},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        audit::{AuditBmc, AuditEvent, AuditFilter},
+        user::{Permission, Role, User, UserBmc},
+        ListOptions, ModelController, Page,
+    },
+    web::mw_auth::mw_require_permission,
+    Result,
+};
+
+/// Admin routes, the user ones require `Permission::UserManage`,
+/// the audit feed `Permission::AuditRead`.
+pub fn routes(mc: ModelController) -> Router {
+    let routes_users = Router::new()
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/:id/role", patch(update_user_role))
+        .route_layer(middleware::from_fn_with_state(
+            Permission::UserManage,
+            mw_require_permission,
+        ));
+
+    let routes_audit = Router::new()
+        .route("/admin/audit", get(list_audit_events))
+        .route_layer(middleware::from_fn_with_state(
+            Permission::AuditRead,
+            mw_require_permission,
+        ));
+
+    routes_users.merge(routes_audit).with_state(mc)
+}
+
+// region:    --- REST Handlers
+async fn list_users(State(mc): State<ModelController>) -> Result<Json<Vec<User>>> {
+    debug!("{:<12} - list_users", "HANDLER");
+
+    let users = UserBmc::list(&mc).await?;
+
+    Ok(Json(users))
+}
+
+#[derive(Deserialize)]
+struct UserRoleForUpdate {
+    role: Role,
+}
+
+async fn update_user_role(
+    State(mc): State<ModelController>,
+    Path(id): Path<u64>,
+    Json(role_fu): Json<UserRoleForUpdate>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - update_user_role", "HANDLER");
+
+    UserBmc::update_role(&mc, id, role_fu.role).await?;
+
+    // Create the success body
+    let body = Json(json!({
+        "result": {
+            "success": true,
+        }
+    }));
+    Ok(body)
+}
+/// e.g., `GET /api/admin/audit?ticket_id=3&actor_id=1&limit=20&offset=40` (newest first)
+async fn list_audit_events(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Query(filter): Query<AuditFilter>,
+    Query(list_options): Query<ListOptions>,
+) -> Result<Json<Page<AuditEvent>>> {
+    debug!("{:<12} - list_audit_events", "HANDLER");
+
+    let page = AuditBmc::list(&mc, &ctx, filter, list_options).await?;
+
+    Ok(Json(page))
+}
+// endregion: --- REST Handlers
@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get},
+    Json, Router,
+};
+use tracing::debug;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        api_key::{ApiKey, ApiKeyBmc, ApiKeyCreated, ApiKeyForCreate},
+        ModelController,
+    },
+    Result,
+};
+
+/// Api keys of the ctx user (for the `Authorization: Bearer <api-key>` header).
+/// Requires `Permission::ApiKeyManage`, which an api key ctx never has.
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/keys", get(list_api_keys).post(create_api_key))
+        .route("/keys/:id", delete(revoke_api_key))
+        .with_state(mc)
+}
+
+// region:    --- REST Handlers
+/// The clear key is only in this response.
+async fn create_api_key(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Json(api_key_fc): Json<ApiKeyForCreate>,
+) -> Result<Json<ApiKeyCreated>> {
+    debug!("{:<12} - create_api_key", "HANDLER");
+
+    let api_key = ApiKeyBmc::create(&mc, &ctx, api_key_fc).await?;
+
+    Ok(Json(api_key))
+}
+
+async fn list_api_keys(State(mc): State<ModelController>, ctx: Ctx) -> Result<Json<Vec<ApiKey>>> {
+    debug!("{:<12} - list_api_keys", "HANDLER");
+
+    let api_keys = ApiKeyBmc::list(&mc, &ctx).await?;
+
+    Ok(Json(api_keys))
+}
+
+async fn revoke_api_key(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<String>,
+) -> Result<Json<ApiKey>> {
+    debug!("{:<12} - revoke_api_key", "HANDLER");
+
+    let api_key = ApiKeyBmc::revoke(&mc, &ctx, &id).await?;
+
+    Ok(Json(api_key))
+}
+// endregion: --- REST Handlers
@@ -0,0 +1,70 @@
+//! Operations endpoints (no auth, not under `/api`)
+//! - `GET /healthz` - liveness, always 200 while the process serves (store status for info).
+//! - `GET /readyz`  - readiness, 503 when the model store is not reachable.
+//! - `GET /metrics` - Prometheus metrics (see `mw_metrics`).
+
+use axum::{
+    extract::{FromRef, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::{model::ModelController, web::mw_metrics::Metrics};
+
+#[derive(Clone, FromRef)]
+struct HealthState {
+    mc: ModelController,
+    metrics: Metrics,
+}
+
+pub fn routes(mc: ModelController, metrics: Metrics) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics_text))
+        .with_state(HealthState { mc, metrics })
+}
+
+/// Not failing on the store, so a store outage does not restart the service.
+async fn healthz(State(mc): State<ModelController>) -> impl IntoResponse {
+    debug!("{:<12} - healthz", "HANDLER");
+
+    let store = if mc.ping_store().await.is_ok() {
+        "ok"
+    } else {
+        "unreachable"
+    };
+
+    Json(json!({
+        "status": "ok",
+        "store": store,
+    }))
+}
+
+async fn readyz(State(mc): State<ModelController>) -> impl IntoResponse {
+    debug!("{:<12} - readyz", "HANDLER");
+
+    match mc.ping_store().await {
+        Ok(()) => (StatusCode::OK, Json(json!({"status": "ready"}))),
+        Err(ex) => {
+            warn!(error = ?ex, "{:<12} - readyz - store unreachable", "HEALTH");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"status": "unavailable", "store": "unreachable"})),
+            )
+        }
+    }
+}
+
+async fn metrics_text(State(metrics): State<Metrics>) -> impl IntoResponse {
+    debug!("{:<12} - metrics", "HANDLER");
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
@@ -1,26 +1,229 @@
-use crate::{web, Error, Result};
-use axum::{routing::post, Json, Router};
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    config::Config,
+    crypt::token::{now_unix_sec, validate_token, Token},
+    ctx::Ctx,
+    model::{
+        session::SessionBmc,
+        user::{User, UserBmc, UserForCreate},
+        ModelController,
+    },
+    web::{
+        self,
+        mw_rate_limit::{mw_rate_limit, LoginLockout, RateLimiter},
+        routes_openapi::ErrorBody,
+    },
+    Error, Result,
+};
+use axum::{
+    extract::{FromRef, State},
+    middleware,
+    routing::post,
+    Json, Router,
+};
 use serde::Deserialize;
 use serde_json::{json, Value};
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
+use tracing::debug;
+
+#[derive(Clone, FromRef)]
+struct LoginState {
+    mc: ModelController,
+    config: Arc<Config>,
+    username_limiter: RateLimiter,
+    lockout: LoginLockout,
+}
+
+pub fn routes(mc: ModelController, config: Arc<Config>) -> Router {
+    let state = LoginState {
+        mc,
+        config: config.clone(),
+        username_limiter: RateLimiter::per_min(config.rate_login_per_min),
+        lockout: LoginLockout::new(
+            config.login_lockout_failures,
+            Duration::from_secs(config.login_lockout_window_sec),
+            Duration::from_secs(config.login_lockout_sec),
+        ),
+    };
 
-pub fn routes() -> Router {
     Router::new()
-        .route("/api/login", post(api_login))
-        .with_state(())
+        .route(
+            "/api/login",
+            // Per client ip (the per username limit is in the handler).
+            post(api_login).route_layer(middleware::from_fn_with_state(
+                RateLimiter::per_min(config.rate_login_per_min),
+                mw_rate_limit,
+            )),
+        )
+        .route("/api/logoff", post(api_logoff))
+        .route("/api/register", post(api_register))
+        .route("/api/pwd", post(api_pwd_change))
+        .with_state(state)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/login",
+    tag = "login",
+    security(()),
+    request_body = LoginPayload,
+    responses(
+        (status = 200, description = "Logged in, the `auth-token` cookie is set",
+            example = json!({"result": {"success": true}})),
+        (status = 403, description = "Wrong username or password", body = ErrorBody),
+        (status = 429, description = "Too many attempts or account locked (see `Retry-After`)", body = ErrorBody),
+    )
+)]
+async fn api_login(
+    State(mc): State<ModelController>,
+    State(config): State<Arc<Config>>,
+    State(username_limiter): State<RateLimiter>,
+    State(lockout): State<LoginLockout>,
+    cookies: Cookies,
+    Json(payload): Json<LoginPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login", "HANDLER");
+
+    let LoginPayload { username, password } = payload;
+
+    username_limiter.check(&username)?;
+    lockout.check(&username)?;
+
+    let user = match UserBmc::login(&mc, &username, password).await {
+        Ok(user) => {
+            lockout.record_success(&username)?;
+            user
+        }
+        Err(ex) => {
+            if matches!(ex, Error::LoginFail) {
+                lockout.record_failure(&username)?;
+            }
+            return Err(ex);
+        }
+    };
+
+    // Set the signed auth token cookie.
+    web::set_token_cookie(&config, &cookies, &user)?;
+
+    // Create the success body
+    let body = Json(json!({
+        "result": {
+            "success": true,
+        }
+    }));
+    Ok(body)
 }
 
-async fn api_login(cookies: Cookies, payload: Json<LoginPayload>) -> Result<Json<Value>> {
-    println!("->> {:<12} - api_login", "HANDLER");
+/// Revokes the session of the auth cookie (if valid) and removes the cookie.
+#[utoipa::path(
+    post,
+    path = "/api/logoff",
+    tag = "login",
+    security(()),
+    responses(
+        (status = 200, description = "Session revoked and cookie removed",
+            example = json!({"result": {"logged_off": true}})),
+    )
+)]
+async fn api_logoff(
+    State(mc): State<ModelController>,
+    State(config): State<Arc<Config>>,
+    cookies: Cookies,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_logoff", "HANDLER");
 
-    // TODO: Implement real db/auth logic
-    if payload.username != "demo1" || payload.password != "welcome" {
-        return Err(Error::LoginFail);
+    let token = cookies
+        .get(&config.auth_cookie)
+        .and_then(|c| c.value().parse::<Token>().ok());
+
+    // Only a valid token is revoked (a removed user has no session left).
+    let token = match token {
+        Some(token) => match UserBmc::get(&mc, token.user_id).await {
+            Ok(user) => validate_token(&token, &web::session_key(&config, &user))
+                .is_ok()
+                .then_some(token),
+            Err(Error::UserNotFound { .. }) => None,
+            Err(ex) => return Err(ex),
+        },
+        None => None,
+    };
+
+    // Revoked until the latest expiration a refreshed token of this session can have.
+    if let Some(token) = token {
+        let until = now_unix_sec() + config.token_duration_sec;
+        SessionBmc::revoke(&mc, &token.token_id, until).await?;
     }
 
-    // FIXME: Implement real auth token generation/signature.
-    // cookies.add(Cookie::new("auth-token", "user-1.exp.sign"));
-    cookies.add(Cookie::new(web::AUTH_TOKEN, "user-1.exp.sign"));
+    web::remove_token_cookie(&config, &cookies);
+
+    // Create the success body
+    let body = Json(json!({
+        "result": {
+            "logged_off": true,
+        }
+    }));
+    Ok(body)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/register",
+    tag = "login",
+    security(()),
+    request_body = LoginPayload,
+    responses(
+        (status = 200, body = User),
+        (status = 400, description = "Invalid params", body = ErrorBody),
+    )
+)]
+async fn api_register(
+    State(mc): State<ModelController>,
+    Json(payload): Json<LoginPayload>,
+) -> Result<Json<User>> {
+    debug!("{:<12} - api_register", "HANDLER");
+
+    let LoginPayload { username, password } = payload;
+    let user = UserBmc::create(
+        &mc,
+        UserForCreate {
+            username,
+            pwd_clear: password,
+        },
+    )
+    .await?;
+
+    Ok(Json(user))
+}
+
+/// Also ends all the sessions of the user (see `web::session_key`), the current
+/// client gets a new one. The api keys are kept.
+#[utoipa::path(
+    post,
+    path = "/api/pwd",
+    tag = "login",
+    request_body = PwdChangePayload,
+    responses(
+        (status = 200, example = json!({"result": {"success": true}})),
+        (status = 403, description = "No auth or access denied", body = ErrorBody),
+    )
+)]
+async fn api_pwd_change(
+    State(mc): State<ModelController>,
+    State(config): State<Arc<Config>>,
+    ctx: Ctx,
+    cookies: Cookies,
+    Json(payload): Json<PwdChangePayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_pwd_change", "HANDLER");
+
+    UserBmc::update_pwd(&mc, &ctx, payload.password_old, payload.password_new).await?;
+
+    // New session (new password hash) for a cookie client, not for an api key.
+    if cookies.get(&config.auth_cookie).is_some() {
+        let user = UserBmc::get(&mc, ctx.user_id()).await?;
+        web::set_token_cookie(&config, &cookies, &user)?;
+    }
 
     // Create the success body
     let body = Json(json!({
@@ -31,8 +234,14 @@ async fn api_login(cookies: Cookies, payload: Json<LoginPayload>) -> Result<Json
     Ok(body)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 struct LoginPayload {
     username: String,
     password: String,
 }
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+struct PwdChangePayload {
+    password_old: String,
+    password_new: String,
+}
@@ -0,0 +1,252 @@
+//! OpenAPI 3 document of the login and ticket apis
+//! (generated from the handler `#[utoipa::path]` annotations and the payload types).
+//! - `GET /api/openapi.json` - the document.
+//! - `GET /api/docs`         - interactive docs (Scalar, loaded from its cdn).
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::Html, routing::get, Json, Router};
+use serde::Serialize;
+use tracing::debug;
+use utoipa::{
+    openapi::{
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        OpenApi as OpenApiDoc,
+    },
+    IntoResponses, Modify, OpenApi, ToSchema,
+};
+use utoipa_scalar::Scalar;
+
+use crate::{config::Config, error::ClientError, web::AUTH_TOKEN};
+
+use super::{routes_login, routes_ticket_events, routes_tickets};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "jeremy-chone-axum", description = "Ticket service api"),
+    paths(
+        // -- Login
+        routes_login::api_login,
+        routes_login::api_logoff,
+        routes_login::api_register,
+        routes_login::api_pwd_change,
+        // -- Tickets
+        routes_tickets::create_ticket,
+        routes_tickets::list_tickets,
+        routes_tickets::get_ticket,
+        routes_tickets::update_ticket,
+        routes_tickets::delete_ticket,
+        routes_tickets::transition_ticket,
+        routes_tickets::list_ticket_history,
+        routes_tickets::list_trash,
+        routes_tickets::restore_ticket,
+        routes_tickets::list_ticket_shares,
+        routes_tickets::share_ticket,
+        routes_tickets::unshare_ticket,
+        routes_tickets::create_comment,
+        routes_tickets::list_comments,
+        routes_tickets::update_comment,
+        routes_tickets::delete_comment,
+        // -- Ticket Events
+        routes_ticket_events::ticket_events_sse,
+        routes_ticket_events::ticket_events_ws,
+    ),
+    // Also referenced by the `IntoResponses` types (not collected from them).
+    components(schemas(ErrorBody)),
+    modifiers(&SecurityAddon),
+    security(("cookie" = []), ("api_key" = [])),
+    tags(
+        (name = "login", description = "Session cookie login (no auth required, except `/api/pwd`)"),
+        (name = "tickets"),
+        (name = "ticket-shares"),
+        (name = "ticket-comments"),
+        (name = "ticket-events", description = "Live ticket events"),
+    )
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(AUTH_TOKEN))),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("ak-<key-id>.<secret>")
+                    .build(),
+            ),
+        );
+    }
+}
+
+// region:    --- Error Body
+/// The error body built by `main_response_mapper` (for the REST apis).
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorDetail {
+    pub r#type: ClientError,
+    /// The request id (also in the `X-Request-Id` response header).
+    pub req_uuid: String,
+}
+
+/// The error responses of the authenticated apis.
+#[derive(IntoResponses)]
+pub enum AuthErrors {
+    #[response(status = 403, description = "No auth or access denied")]
+    Forbidden(ErrorBody),
+}
+
+/// The error responses of the apis on a live ticket (`/api/tickets/{id}/...`).
+#[derive(IntoResponses)]
+pub enum TicketErrors {
+    #[response(status = 403, description = "No auth or access denied")]
+    Forbidden(ErrorBody),
+    #[response(status = 404, description = "Ticket not found (or in the trash)")]
+    NotFound(ErrorBody),
+}
+
+/// The error responses of the apis on a comment of a live ticket.
+#[derive(IntoResponses)]
+pub enum CommentErrors {
+    #[response(status = 403, description = "No auth or access denied")]
+    Forbidden(ErrorBody),
+    #[response(
+        status = 404,
+        description = "Ticket not found (or in the trash), or comment not found on this ticket"
+    )]
+    NotFound(ErrorBody),
+}
+// endregion: --- Error Body
+
+pub fn routes(config: Arc<Config>) -> Router {
+    Router::new()
+        .route("/api/openapi.json", get(openapi_json))
+        .route("/api/docs", get(openapi_docs))
+        .with_state(config)
+}
+
+/// `ApiDoc` with the configured auth cookie name.
+fn api_doc(config: &Config) -> OpenApiDoc {
+    let mut doc = ApiDoc::openapi();
+    if let Some(components) = doc.components.as_mut() {
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(&config.auth_cookie))),
+        );
+    }
+
+    doc
+}
+
+async fn openapi_json(State(config): State<Arc<Config>>) -> Json<OpenApiDoc> {
+    debug!("{:<12} - openapi_json", "HANDLER");
+
+    Json(api_doc(&config))
+}
+
+async fn openapi_docs(State(config): State<Arc<Config>>) -> Html<String> {
+    debug!("{:<12} - openapi_docs", "HANDLER");
+
+    Html(Scalar::new(api_doc(&config)).to_html())
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use lazy_regex::regex;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::model::ModelController;
+
+    /// The documented route modules, mounted as in `routes_all`.
+    async fn documented_routes() -> crate::Result<Router> {
+        let mc = ModelController::new(None).await?;
+        let config = Arc::new(Config::for_test());
+
+        Ok(routes_login::routes(mc.clone(), config).nest(
+            "/api",
+            routes_tickets::routes(mc.clone()).merge(routes_ticket_events::routes(mc)),
+        ))
+    }
+
+    /// The route paths of a router.
+    /// NOTE: axum has no route listing, but the `Debug` of its path router has the paths.
+    fn router_paths(router: &Router) -> BTreeSet<String> {
+        let debug = format!("{router:?}");
+        let path_router = debug.split("fallback_router").next().unwrap_or_default();
+
+        regex!(r#"RouteId\(\d+\): "([^"]+)""#)
+            .captures_iter(path_router)
+            .map(|c| c[1].to_string())
+            .collect()
+    }
+
+    /// Every route of the documented modules is in the spec, and vice versa.
+    #[tokio::test]
+    async fn test_openapi_paths_match_routes() -> crate::Result<()> {
+        let app = documented_routes().await?;
+
+        let route_paths: BTreeSet<String> = router_paths(&app)
+            .iter()
+            // axum `:id` to openapi `{id}`
+            .map(|path| regex!(r":(\w+)").replace_all(path, "{$1}").to_string())
+            .collect();
+        let spec_paths: BTreeSet<String> = ApiDoc::openapi().paths.paths.into_keys().collect();
+
+        assert_eq!(route_paths, spec_paths);
+
+        Ok(())
+    }
+
+    /// Every spec operation is routed (not a 404 nor 405 from the router).
+    #[tokio::test]
+    async fn test_openapi_operations_routed() -> crate::Result<()> {
+        let app = documented_routes().await?;
+
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let operations = [
+                (Method::GET, item.get.is_some()),
+                (Method::POST, item.post.is_some()),
+                (Method::PATCH, item.patch.is_some()),
+                (Method::PUT, item.put.is_some()),
+                (Method::DELETE, item.delete.is_some()),
+            ];
+            let uri = regex!(r"\{\w+\}").replace_all(&path, "1").to_string();
+
+            for (method, _) in operations.into_iter().filter(|(_, documented)| *documented) {
+                let req = Request::builder()
+                    .method(method.clone())
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap();
+                let status = app.clone().oneshot(req).await.unwrap().status();
+
+                assert!(
+                    status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+                    "{method} {path} not routed ({status})"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,217 @@
+//! JSON-RPC 2.0 endpoint for the ticket operations.
+//!
+//! Errors are turned into JSON-RPC error objects by `main_response_mapper`,
+//! which finds the `RpcInfo` in the response extensions.
+//!
+//! Notifications (requests without `id`) get an empty `204 No Content`, errors included.
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        ListOptions, ModelController, TicketFilter, TicketForCreate, TicketForTransition,
+        TicketForUpdate,
+    },
+    Error, Result,
+};
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/rpc", post(rpc_handler))
+        .with_state(mc)
+}
+
+// region:    --- RPC Types
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    /// `None` when absent (notification), `Some(Value::Null)` for `"id": null`.
+    #[serde(default, deserialize_with = "deserialize_id")]
+    id: Option<Value>,
+    method: String,
+    params: Option<Value>,
+}
+
+/// Stored in the response extensions for the `main_response_mapper` and the request log.
+#[derive(Clone, Debug)]
+pub struct RpcInfo {
+    pub id: Option<Value>,
+    pub method: Option<String>,
+    /// No response body expected (see `main_response_mapper`).
+    pub notification: bool,
+}
+
+#[derive(Deserialize)]
+struct ParamsForCreate<D> {
+    data: D,
+}
+
+#[derive(Deserialize)]
+struct ParamsForUpdate<D> {
+    id: u64,
+    data: D,
+}
+
+#[derive(Deserialize)]
+struct ParamsIded {
+    id: u64,
+}
+
+#[derive(Default, Deserialize)]
+struct ParamsList {
+    filter: Option<TicketFilter>,
+    list_options: Option<ListOptions>,
+}
+// endregion: --- RPC Types
+
+async fn rpc_handler(
+    State(mc): State<ModelController>,
+    ctx: Result<Ctx>,
+    rpc_req: core::result::Result<Json<RpcRequest>, JsonRejection>,
+) -> Response {
+    // -- Parse the request envelope (id might not be known).
+    let rpc_req = match rpc_req {
+        Ok(Json(rpc_req)) if rpc_req.jsonrpc == "2.0" => rpc_req,
+        Ok(Json(rpc_req)) => {
+            return rpc_error_response(rpc_req.id, Error::RpcInvalidRequest);
+        }
+        // Not JSON at all (vs. JSON, but not a request object).
+        Err(JsonRejection::JsonSyntaxError(_)) => {
+            return rpc_error_response(None, Error::RpcParseError)
+        }
+        Err(_) => return rpc_error_response(None, Error::RpcInvalidRequest),
+    };
+
+    let RpcRequest {
+        id, method, params, ..
+    } = rpc_req;
+
+    debug!("{:<12} - rpc_handler - {method}", "HANDLER");
+
+    let result = match ctx {
+        Ok(ctx) => rpc_dispatch(mc, ctx, &method, params).await,
+        Err(ex) => Err(ex),
+    };
+
+    let rpc_info = RpcInfo {
+        notification: id.is_none(),
+        id,
+        method: Some(method),
+    };
+    let mut res = match result {
+        Ok(_) if rpc_info.notification => StatusCode::NO_CONTENT.into_response(),
+        Ok(result) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": rpc_info.id,
+            "result": result,
+        }))
+        .into_response(),
+        // The error body is built by the `main_response_mapper`.
+        Err(ex) => ex.into_response(),
+    };
+    res.extensions_mut().insert(rpc_info);
+
+    res
+}
+
+async fn rpc_dispatch(
+    mc: ModelController,
+    ctx: Ctx,
+    method: &str,
+    params: Option<Value>,
+) -> Result<Value> {
+    let result_json: Value = match method {
+        "create_ticket" => {
+            let ParamsForCreate::<TicketForCreate> { data } = parse_params(method, params)?;
+            json!(mc.create_ticket(ctx, data).await?)
+        }
+        "get_ticket" => {
+            let ParamsIded { id } = parse_params(method, params)?;
+            json!(mc.get_ticket(ctx, id).await?)
+        }
+        "list_tickets" => {
+            let ParamsList {
+                filter,
+                list_options,
+            } = parse_params_or_default(method, params)?;
+            json!(
+                mc.list_tickets(
+                    ctx,
+                    filter.unwrap_or_default(),
+                    list_options.unwrap_or_default()
+                )
+                .await?
+            )
+        }
+        "update_ticket" => {
+            let ParamsForUpdate::<TicketForUpdate> { id, data } = parse_params(method, params)?;
+            json!(mc.update_ticket(ctx, id, data).await?)
+        }
+        "transition_ticket" => {
+            let ParamsForUpdate::<TicketForTransition> { id, data } = parse_params(method, params)?;
+            json!(mc.transition_ticket(ctx, id, data).await?)
+        }
+        "delete_ticket" => {
+            let ParamsIded { id } = parse_params(method, params)?;
+            json!(mc.delete_ticket(ctx, id).await?)
+        }
+
+        // -- Fallback as Err.
+        _ => return Err(Error::RpcMethodUnknown(method.to_string())),
+    };
+
+    Ok(result_json)
+}
+
+// region:    --- Support
+/// Keeps an explicit `"id": null` (a request, not a notification).
+fn deserialize_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+fn parse_params<P: DeserializeOwned>(method: &str, params: Option<Value>) -> Result<P> {
+    let params = params.ok_or_else(|| Error::RpcMissingParams {
+        rpc_method: method.to_string(),
+    })?;
+
+    serde_json::from_value(params).map_err(|_| Error::RpcFailJsonParams {
+        rpc_method: method.to_string(),
+    })
+}
+
+fn parse_params_or_default<P: DeserializeOwned + Default>(
+    method: &str,
+    params: Option<Value>,
+) -> Result<P> {
+    match params {
+        None => Ok(P::default()),
+        Some(params) => parse_params(method, Some(params)),
+    }
+}
+
+/// Invalid request envelope, always answered (even without `id`).
+///
+/// The error is stored in the response extensions (see `Error::into_response`),
+/// the `main_response_mapper` builds the JSON-RPC error body.
+fn rpc_error_response(id: Option<Value>, ex: Error) -> Response {
+    let mut res = ex.into_response();
+    res.extensions_mut().insert(RpcInfo {
+        id,
+        method: None,
+        notification: false,
+    });
+    res
+}
+// endregion: --- Support
@@ -0,0 +1,142 @@
+//! Static files of the web folder (`Config::web_folder`), the app router fallback
+//! - Nothing outside the web folder is served (`..` paths are rejected by `ServeDir`).
+//! - Precompressed `<file>.br` / `<file>.gz` siblings per `Accept-Encoding`.
+//! - SPA fallback - the paths without a file extension get the `index.html`.
+//! - `Last-Modified`, weak `ETag` (`If-None-Match` -> 304) and `Cache-Control`.
+
+use std::{
+    convert::Infallible,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get_service,
+    Router,
+};
+use tower::{service_fn, ServiceExt};
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::debug;
+
+use crate::config::Config;
+
+/// `Cache-Control` of the html pages, always revalidated (they link the assets).
+const CACHE_CONTROL_HTML: &str = "no-cache";
+/// `Cache-Control` of the other files.
+const CACHE_CONTROL_ASSET: &str = "public, max-age=3600";
+
+pub fn routes(config: &Config) -> Router {
+    let index = config.web_folder.join("index.html");
+
+    let spa_fallback = service_fn(move |req: Request| {
+        let index = index.clone();
+        async move {
+            if !is_spa_route(&req) {
+                return Ok::<_, Infallible>(StatusCode::NOT_FOUND.into_response());
+            }
+            let res = ServeFile::new(index)
+                .precompressed_br()
+                .precompressed_gzip()
+                .oneshot(req)
+                .await?;
+
+            Ok(res.map(Body::new))
+        }
+    });
+
+    let serve_dir = ServeDir::new(&config.web_folder)
+        .precompressed_br()
+        .precompressed_gzip()
+        .fallback(spa_fallback);
+
+    Router::new()
+        .nest_service("/", get_service(serve_dir))
+        .layer(middleware::from_fn(mw_static_cache))
+}
+
+/// e.g., `/tickets/42` (client side route), but not `/app.js` (missing asset).
+fn is_spa_route(req: &Request) -> bool {
+    let method_ok = req.method() == Method::GET || req.method() == Method::HEAD;
+    let last_segment = req.uri().path().rsplit('/').next().unwrap_or_default();
+
+    method_ok && !last_segment.contains('.')
+}
+
+async fn mw_static_cache(req: Request, next: Next) -> Response {
+    debug!("{:<12} - mw_static_cache", "MIDDLEWARE");
+
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+
+    let mut res = next.run(req).await;
+    if !matches!(res.status(), StatusCode::OK | StatusCode::NOT_MODIFIED) {
+        return res;
+    }
+
+    let etag = etag(&res);
+    let is_html = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+
+    if let (Some(etag), Some(if_none_match)) = (&etag, &if_none_match) {
+        if if_none_match_matches(if_none_match, etag) {
+            res = StatusCode::NOT_MODIFIED.into_response();
+        }
+    }
+
+    let headers = res.headers_mut();
+    if let Some(etag) = etag {
+        headers.insert(header::ETAG, etag);
+    }
+    let cache_control = if is_html {
+        CACHE_CONTROL_HTML
+    } else {
+        CACHE_CONTROL_ASSET
+    };
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    // The precompressed variants have their own body (and ETag).
+    headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+    res
+}
+
+/// Weak ETag from the `Last-Modified` and `Content-Length` of a 200 response
+/// (so differs per precompressed variant).
+fn etag(res: &Response) -> Option<HeaderValue> {
+    if res.status() != StatusCode::OK {
+        return None;
+    }
+    let last_modified = res.headers().get(header::LAST_MODIFIED)?;
+    let content_length = res.headers().get(header::CONTENT_LENGTH)?;
+
+    let mut hasher = DefaultHasher::new();
+    last_modified.as_bytes().hash(&mut hasher);
+    content_length.as_bytes().hash(&mut hasher);
+    res.headers()
+        .get(header::CONTENT_ENCODING)
+        .map(HeaderValue::as_bytes)
+        .hash(&mut hasher);
+
+    HeaderValue::from_str(&format!("W/\"{:016x}\"", hasher.finish())).ok()
+}
+
+/// `If-None-Match` is a list of ETags (or `*`), compared weakly.
+fn if_none_match_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
+        return false;
+    };
+    let etag = etag.to_str().unwrap_or_default().trim_start_matches("W/");
+
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
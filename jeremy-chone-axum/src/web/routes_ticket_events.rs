@@ -0,0 +1,124 @@
+//! Live ticket events (see `model::event`), one JSON `TicketEvent` per message.
+//! - `GET /api/tickets/events`    - Server-Sent Events (event name is the kind).
+//! - `GET /api/tickets/events/ws` - WebSocket (text messages, client messages ignored).
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    routing::get,
+    Router,
+};
+use futures_util::{stream, Stream};
+use tracing::debug;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        event::{TicketEvent, TicketEventBmc, TicketEventSubscription},
+        ModelController,
+    },
+    web::routes_openapi::AuthErrors,
+    Result,
+};
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/tickets/events", get(ticket_events_sse))
+        .route("/tickets/events/ws", get(ticket_events_ws))
+        .with_state(mc)
+}
+
+// region:    --- SSE
+#[utoipa::path(
+    get,
+    path = "/api/tickets/events",
+    tag = "ticket-events",
+    responses(
+        (status = 200, description = "`text/event-stream`, one `TicketEvent` json per event",
+            content_type = "text/event-stream", body = TicketEvent),
+        AuthErrors,
+    )
+)]
+async fn ticket_events_sse(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+) -> Result<Sse<impl Stream<Item = core::result::Result<Event, Infallible>>>> {
+    debug!("{:<12} - ticket_events_sse", "HANDLER");
+
+    let subscription = TicketEventBmc::subscribe(&mc, ctx)?;
+
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        let sse_event = Event::default()
+            .event(event.kind.as_ref())
+            .data(serde_json::to_string(&event).ok()?);
+        Some((Ok(sse_event), subscription))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+// endregion: --- SSE
+
+// region:    --- WebSocket
+#[utoipa::path(
+    get,
+    path = "/api/tickets/events/ws",
+    tag = "ticket-events",
+    responses(
+        (status = 101, description = "WebSocket, one `TicketEvent` json per text message",
+            body = TicketEvent),
+        AuthErrors,
+    )
+)]
+async fn ticket_events_ws(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ws: WebSocketUpgrade,
+) -> Result<Response> {
+    debug!("{:<12} - ticket_events_ws", "HANDLER");
+
+    let subscription = TicketEventBmc::subscribe(&mc, ctx)?;
+
+    Ok(ws.on_upgrade(|socket| send_ticket_events(socket, subscription)))
+}
+
+/// Until the client closes the socket (or the subscription ends, e.g., on shutdown).
+async fn send_ticket_events(mut socket: WebSocket, mut subscription: TicketEventSubscription) {
+    loop {
+        // NOTE: Only the cancel safe `recv` is raced (an event taken is never dropped),
+        //       the visibility check is done out of the `select!`.
+        tokio::select! {
+            event = subscription.recv() => {
+                let Some(event) = event else {
+                    let close = CloseFrame {
+                        code: close_code::AWAY,
+                        reason: "server shutdown".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    break;
+                };
+                if !subscription.is_visible(&event).await {
+                    continue;
+                }
+                let Ok(json) = serde_json::to_string(&event) else { continue };
+                if socket.send(Message::Text(json)).await.is_err() {
+                    break;
+                }
+            }
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum, the rest is ignored.
+                Some(Ok(_)) => (),
+            },
+        }
+    }
+}
+// endregion: --- WebSocket
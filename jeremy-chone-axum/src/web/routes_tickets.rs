@@ -1,51 +1,432 @@
 use axum::{
-    extract::{Path, State},
-    routing::{delete, post},
+    extract::{Path, Query, State},
+    routing::{delete, get, patch, post},
     Json, Router,
 };
+use serde::Deserialize;
+use tracing::debug;
 
 use crate::{
-    model::{ModelController, Ticket, TicketForCreate},
-    Result, ctx::Ctx,
+    ctx::Ctx,
+    model::{
+        audit::{AuditBmc, AuditEvent},
+        comment::{Comment, CommentBmc, CommentForCreate, CommentForUpdate},
+        ListOptions, ModelController, Page, Ticket, TicketFilter, TicketForCreate,
+        TicketForTransition, TicketForUpdate,
+    },
+    web::routes_openapi::{AuthErrors, CommentErrors, ErrorBody, TicketErrors},
+    Result,
 };
 
 pub fn routes(mc: ModelController) -> Router {
     Router::new()
         .route("/tickets", post(create_ticket).get(list_tickets))
-        .route("/tickets/:id", delete(delete_ticket))
+        // Static segment, takes precedence over `/tickets/:id`.
+        .route("/tickets/trash", get(list_trash))
+        .route(
+            "/tickets/:id",
+            get(get_ticket).patch(update_ticket).delete(delete_ticket),
+        )
+        .route("/tickets/:id/transition", post(transition_ticket))
+        .route("/tickets/:id/restore", post(restore_ticket))
+        .route("/tickets/:id/history", get(list_ticket_history))
+        .route(
+            "/tickets/:id/shares",
+            get(list_ticket_shares).post(share_ticket),
+        )
+        .route("/tickets/:id/shares/:user_id", delete(unshare_ticket))
+        .route(
+            "/tickets/:id/comments",
+            get(list_comments).post(create_comment),
+        )
+        .route(
+            "/tickets/:id/comments/:comment_id",
+            patch(update_comment).delete(delete_comment),
+        )
         .with_state(mc)
 }
 
 // region:    --- REST Handlers
+#[utoipa::path(
+    post,
+    path = "/api/tickets",
+    tag = "tickets",
+    request_body = TicketForCreate,
+    responses(
+        (status = 200, body = Ticket),
+        AuthErrors,
+    )
+)]
 async fn create_ticket(
     State(mc): State<ModelController>,
     ctx: Ctx,
     Json(ticket_fc): Json<TicketForCreate>,
 ) -> Result<Json<Ticket>> {
-    println!("->> {:<12} - create_ticket", "HANDLER");
+    debug!("{:<12} - create_ticket", "HANDLER");
 
     let ticket = mc.create_ticket(ctx, ticket_fc).await?;
 
     Ok(Json(ticket))
 }
 
-async fn list_tickets(State(mc): State<ModelController>, ctx: Ctx) -> Result<Json<Vec<Ticket>>> {
-    println!("->> {:<12} - list_tickets", "HANDLER");
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{id}",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id")),
+    responses(
+        (status = 200, body = Ticket),
+        TicketErrors,
+    )
+)]
+async fn get_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+) -> Result<Json<Ticket>> {
+    debug!("{:<12} - get_ticket", "HANDLER");
+
+    let ticket = mc.get_ticket(ctx, id).await?;
+
+    Ok(Json(ticket))
+}
+
+/// e.g., `GET /api/tickets?title=bug&cid=1&status=open&sort=-id&limit=20&offset=40`
+#[utoipa::path(
+    get,
+    path = "/api/tickets",
+    tag = "tickets",
+    params(TicketFilter, ListOptions),
+    responses(
+        (status = 200, body = Page<Ticket>),
+        AuthErrors,
+    )
+)]
+async fn list_tickets(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Query(filter): Query<TicketFilter>,
+    Query(list_options): Query<ListOptions>,
+) -> Result<Json<Page<Ticket>>> {
+    debug!("{:<12} - list_tickets", "HANDLER");
+
+    let page = mc.list_tickets(ctx, filter, list_options).await?;
+
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/tickets/{id}",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id")),
+    request_body = TicketForUpdate,
+    responses(
+        (status = 200, body = Ticket),
+        TicketErrors,
+    )
+)]
+async fn update_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+    Json(ticket_fu): Json<TicketForUpdate>,
+) -> Result<Json<Ticket>> {
+    debug!("{:<12} - update_ticket", "HANDLER");
+
+    let ticket = mc.update_ticket(ctx, id, ticket_fu).await?;
+
+    Ok(Json(ticket))
+}
+
+/// e.g., `POST /api/tickets/1/transition` with `{"status": "in-progress"}`
+#[utoipa::path(
+    post,
+    path = "/api/tickets/{id}/transition",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id")),
+    request_body = TicketForTransition,
+    responses(
+        (status = 200, body = Ticket),
+        (status = 409, description = "Transition not allowed from the current status", body = ErrorBody),
+        TicketErrors,
+    )
+)]
+async fn transition_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+    Json(ticket_ft): Json<TicketForTransition>,
+) -> Result<Json<Ticket>> {
+    debug!("{:<12} - transition_ticket", "HANDLER");
 
-    let tickets = mc.list_tickets(ctx).await?;
+    let ticket = mc.transition_ticket(ctx, id, ticket_ft).await?;
 
-    Ok(Json(tickets))
+    Ok(Json(ticket))
 }
 
+/// Audit events of the ticket, newest first, e.g., `GET /api/tickets/1/history?limit=20&offset=40`
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{id}/history",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id"), ListOptions),
+    responses(
+        (status = 200, body = Page<AuditEvent>),
+        TicketErrors,
+    )
+)]
+async fn list_ticket_history(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+    Query(list_options): Query<ListOptions>,
+) -> Result<Json<Page<AuditEvent>>> {
+    debug!("{:<12} - list_ticket_history", "HANDLER");
+
+    let page = AuditBmc::list_for_ticket(&mc, &ctx, id, list_options).await?;
+
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tickets/{id}",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id")),
+    responses(
+        (status = 200, description = "Moved to the trash", body = Ticket),
+        TicketErrors,
+    )
+)]
 async fn delete_ticket(
     State(mc): State<ModelController>,
     ctx: Ctx,
     Path(id): Path<u64>,
 ) -> Result<Json<Ticket>> {
-    println!("->> {:<12} - delete_ticket", "HANDLER");
+    debug!("{:<12} - delete_ticket", "HANDLER");
 
     let ticket = mc.delete_ticket(ctx, id).await?;
 
     Ok(Json(ticket))
 }
+
+// region:    --- Ticket Trash
+/// The deleted tickets, until purged, e.g., `GET /api/tickets/trash?sort=-id&limit=20`
+#[utoipa::path(
+    get,
+    path = "/api/tickets/trash",
+    tag = "tickets",
+    params(ListOptions),
+    responses(
+        (status = 200, body = Page<Ticket>),
+        AuthErrors,
+    )
+)]
+async fn list_trash(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Query(list_options): Query<ListOptions>,
+) -> Result<Json<Page<Ticket>>> {
+    debug!("{:<12} - list_trash", "HANDLER");
+
+    let page = mc.list_trash(ctx, list_options).await?;
+
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tickets/{id}/restore",
+    tag = "tickets",
+    params(("id" = u64, Path, description = "Ticket id")),
+    responses(
+        (status = 200, body = Ticket),
+        (status = 404, description = "No ticket with this id in the trash", body = ErrorBody),
+        AuthErrors,
+    )
+)]
+async fn restore_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+) -> Result<Json<Ticket>> {
+    debug!("{:<12} - restore_ticket", "HANDLER");
+
+    let ticket = mc.restore_ticket(ctx, id).await?;
+
+    Ok(Json(ticket))
+}
+// endregion: --- Ticket Trash
+
+// region:    --- Ticket Shares
+#[derive(Deserialize, utoipa::ToSchema)]
+struct TicketShareForCreate {
+    user_id: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{id}/shares",
+    tag = "ticket-shares",
+    params(("id" = u64, Path, description = "Ticket id")),
+    responses(
+        (status = 200, description = "Shared with user ids", body = Vec<u64>),
+        TicketErrors,
+    )
+)]
+async fn list_ticket_shares(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<u64>>> {
+    debug!("{:<12} - list_ticket_shares", "HANDLER");
+
+    let user_ids = mc.list_ticket_shares(ctx, id).await?;
+
+    Ok(Json(user_ids))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tickets/{id}/shares",
+    tag = "ticket-shares",
+    params(("id" = u64, Path, description = "Ticket id")),
+    request_body = TicketShareForCreate,
+    responses(
+        (status = 200, description = "Shared with user ids", body = Vec<u64>),
+        TicketErrors,
+    )
+)]
+async fn share_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+    Json(share_fc): Json<TicketShareForCreate>,
+) -> Result<Json<Vec<u64>>> {
+    debug!("{:<12} - share_ticket", "HANDLER");
+
+    let user_ids = mc.share_ticket(ctx, id, share_fc.user_id).await?;
+
+    Ok(Json(user_ids))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tickets/{id}/shares/{user_id}",
+    tag = "ticket-shares",
+    params(("id" = u64, Path, description = "Ticket id"), ("user_id" = u64, Path)),
+    responses(
+        (status = 200, description = "Shared with user ids", body = Vec<u64>),
+        TicketErrors,
+    )
+)]
+async fn unshare_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path((id, user_id)): Path<(u64, u64)>,
+) -> Result<Json<Vec<u64>>> {
+    debug!("{:<12} - unshare_ticket", "HANDLER");
+
+    let user_ids = mc.unshare_ticket(ctx, id, user_id).await?;
+
+    Ok(Json(user_ids))
+}
+// endregion: --- Ticket Shares
+
+// region:    --- Ticket Comments
+#[utoipa::path(
+    post,
+    path = "/api/tickets/{id}/comments",
+    tag = "ticket-comments",
+    params(("id" = u64, Path, description = "Ticket id")),
+    request_body = CommentForCreate,
+    responses(
+        (status = 200, body = Comment),
+        (status = 400, description = "Invalid params", body = ErrorBody),
+        TicketErrors,
+    )
+)]
+async fn create_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+    Json(comment_fc): Json<CommentForCreate>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - create_comment", "HANDLER");
+
+    let comment = CommentBmc::create(&mc, &ctx, id, comment_fc).await?;
+
+    Ok(Json(comment))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{id}/comments",
+    tag = "ticket-comments",
+    params(("id" = u64, Path, description = "Ticket id")),
+    responses(
+        (status = 200, body = Vec<Comment>),
+        TicketErrors,
+    )
+)]
+async fn list_comments(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<Comment>>> {
+    debug!("{:<12} - list_comments", "HANDLER");
+
+    let comments = CommentBmc::list(&mc, &ctx, id).await?;
+
+    Ok(Json(comments))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/tickets/{id}/comments/{comment_id}",
+    tag = "ticket-comments",
+    params(("id" = u64, Path, description = "Ticket id"), ("comment_id" = u64, Path)),
+    request_body = CommentForUpdate,
+    responses(
+        (status = 200, body = Comment),
+        (status = 400, description = "Invalid params", body = ErrorBody),
+        CommentErrors,
+    )
+)]
+async fn update_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path((id, comment_id)): Path<(u64, u64)>,
+    Json(comment_fu): Json<CommentForUpdate>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - update_comment", "HANDLER");
+
+    let comment = CommentBmc::update(&mc, &ctx, id, comment_id, comment_fu).await?;
+
+    Ok(Json(comment))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tickets/{id}/comments/{comment_id}",
+    tag = "ticket-comments",
+    params(("id" = u64, Path, description = "Ticket id"), ("comment_id" = u64, Path)),
+    responses(
+        (status = 200, body = Comment),
+        CommentErrors,
+    )
+)]
+async fn delete_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    Path((id, comment_id)): Path<(u64, u64)>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - delete_comment", "HANDLER");
+
+    let comment = CommentBmc::delete(&mc, &ctx, id, comment_id).await?;
+
+    Ok(Json(comment))
+}
+// endregion: --- Ticket Comments
 // endregion: --- REST Handlers
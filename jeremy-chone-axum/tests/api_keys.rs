@@ -0,0 +1,60 @@
+mod common;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::TestClient;
+
+#[tokio::test]
+async fn test_api_key_auth() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+    let res = client
+        .post(
+            "/api/keys",
+            json!({"name": "ci", "permissions": ["ticket_read"]}),
+        )
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+    let key = res.body["key"].as_str().unwrap().to_string();
+    let bearer = format!("Bearer {key}");
+
+    // -- The key works for its permissions (no cookie).
+    let mut machine = client.new_session();
+    let res = machine
+        .get_with_headers("/api/tickets", &[("authorization", &bearer)])
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+
+    // -- But not for the key management.
+    let res = machine
+        .get_with_headers("/api/keys", &[("authorization", &bearer)])
+        .await?;
+    res.assert_error(StatusCode::FORBIDDEN, "ACCESS_DENIED");
+
+    // -- Wrong secret, same key id.
+    let (key_id, _secret) = key.rsplit_once('.').unwrap();
+    let wrong = format!("Bearer {key_id}.{}", "A".repeat(43));
+    let res = machine
+        .get_with_headers("/api/tickets", &[("authorization", &wrong)])
+        .await?;
+    res.assert_error(StatusCode::FORBIDDEN, "NO_AUTH");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_key_create_no_manage_permission() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    let res = client
+        .post(
+            "/api/keys",
+            json!({"name": "ci", "permissions": ["ticket_read", "api_key_manage"]}),
+        )
+        .await?;
+
+    res.assert_error(StatusCode::BAD_REQUEST, "INVALID_PARAMS");
+
+    Ok(())
+}
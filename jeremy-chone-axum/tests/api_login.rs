@@ -0,0 +1,114 @@
+mod common;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{TestClient, DEMO_PWD};
+
+#[tokio::test]
+async fn test_login_ok() -> Result<()> {
+    let mut client = TestClient::new().await?;
+
+    let res = client.login("demo1", DEMO_PWD).await?;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body, json!({"result": {"success": true}}));
+    assert!(client
+        .cookie("auth-token")
+        .is_some_and(|t| t.starts_with("user-1.")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_login_wrong_pwd() -> Result<()> {
+    let mut client = TestClient::new().await?;
+
+    let res = client.login("demo1", "not-welcome").await?;
+
+    res.assert_error(StatusCode::FORBIDDEN, "LOGIN_FAIL");
+    assert!(client.cookie("auth-token").is_none());
+
+    // Unknown user, same error.
+    let res = client.login("nobody", DEMO_PWD).await?;
+
+    res.assert_error(StatusCode::FORBIDDEN, "LOGIN_FAIL");
+    assert!(client.cookie("auth-token").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_fail_no_cookie() -> Result<()> {
+    let mut client = TestClient::new().await?;
+
+    let res = client.get("/api/tickets").await?;
+
+    res.assert_error(StatusCode::FORBIDDEN, "NO_AUTH");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_fail_tampered_cookie() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+    let token = client.cookie("auth-token").unwrap().to_string();
+    let mut session = client.new_session();
+
+    // Same token, other signature.
+    let (head, _sign) = token.rsplit_once('.').unwrap();
+    session.set_cookie("auth-token", &format!("{head}.AAAA"));
+    let res = session.get("/api/tickets").await?;
+
+    res.assert_error(StatusCode::FORBIDDEN, "NO_AUTH");
+    // The invalid cookie is removed.
+    assert!(session.cookie("auth-token").is_none());
+
+    // The original session still works.
+    let res = client.get("/api/tickets").await?;
+    assert_eq!(res.status, StatusCode::OK);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logoff() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    let res = client.post("/api/logoff", json!({})).await?;
+
+    assert_eq!(res.body, json!({"result": {"logged_off": true}}));
+    assert!(client.cookie("auth-token").is_none());
+    let res = client.get("/api/tickets").await?;
+    res.assert_error(StatusCode::FORBIDDEN, "NO_AUTH");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pwd_change_ends_other_sessions() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+    let mut other = client.new_session();
+    other.login("demo1", DEMO_PWD).await?;
+
+    let res = client
+        .post(
+            "/api/pwd",
+            json!({"password_old": DEMO_PWD, "password_new": "welcome2"}),
+        )
+        .await?;
+    assert_eq!(res.body, json!({"result": {"success": true}}));
+
+    // The current client got a new session, the other one is ended.
+    let res = client.get("/api/tickets").await?;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = other.get("/api/tickets").await?;
+    res.assert_error(StatusCode::FORBIDDEN, "NO_AUTH");
+    assert!(other.cookie("auth-token").is_none());
+
+    let res = other.login("demo1", "welcome2").await?;
+    assert_eq!(res.status, StatusCode::OK);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::TestClient;
+
+#[tokio::test]
+async fn test_rpc_call() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    let res = client
+        .post(
+            "/api/rpc",
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "create_ticket",
+                "params": {"data": {"title": "rpc ticket"}}
+            }),
+        )
+        .await?;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["id"], 1);
+    assert_eq!(res.body["result"]["title"], "rpc ticket");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rpc_parse_error() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    let res = client
+        .post_raw("/api/rpc", r#"{"jsonrpc": "2.0", "#)
+        .await?;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.body["id"], Value::Null);
+    assert_eq!(res.body["error"]["code"], -32700);
+
+    // Valid json, but not a request object.
+    let res = client.post("/api/rpc", json!([1, 2])).await?;
+    assert_eq!(res.body["error"]["code"], -32600);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rpc_notification() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    // -- Executed, no response body.
+    let res = client
+        .post(
+            "/api/rpc",
+            json!({
+                "jsonrpc": "2.0",
+                "method": "create_ticket",
+                "params": {"data": {"title": "notified"}}
+            }),
+        )
+        .await?;
+    assert_eq!(res.status, StatusCode::NO_CONTENT);
+    assert!(res.text.is_empty());
+    let res = client.get("/api/tickets").await?;
+    assert_eq!(res.body["items"][0]["title"], "notified");
+
+    // -- Not even for an error.
+    let res = client
+        .post(
+            "/api/rpc",
+            json!({"jsonrpc": "2.0", "method": "no_such_method"}),
+        )
+        .await?;
+    assert_eq!(res.status, StatusCode::NO_CONTENT);
+    assert!(res.text.is_empty());
+
+    // -- Counted with the status the client got.
+    let res = client.get("/metrics").await?;
+    assert!(res
+        .text
+        .contains(r#"http_requests_total{method="POST",route="/api/rpc",status="204"} 2"#));
+    assert!(!res.text.contains(r#"route="/api/rpc",status="4"#));
+
+    // -- `"id": null` is a request (answered).
+    let res = client
+        .post(
+            "/api/rpc",
+            json!({"jsonrpc": "2.0", "id": null, "method": "no_such_method"}),
+        )
+        .await?;
+    assert_eq!(res.body["id"], Value::Null);
+    assert_eq!(res.body["error"]["code"], -32601);
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use jeremy_chone_axum::model::user::{UserBmc, UserForCreate};
+use serde_json::json;
+
+use common::{TestClient, DEMO_PWD};
+
+#[tokio::test]
+async fn test_ticket_crud() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+
+    // -- Create
+    let res = client
+        .post("/api/tickets", json!({"title": "Ticket AAA"}))
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body,
+        json!({"id": 1, "cid": 1, "title": "Ticket AAA", "status": "open"})
+    );
+
+    // -- Get, List
+    let res = client.get("/api/tickets/1").await?;
+    assert_eq!(res.body["title"], "Ticket AAA");
+    client
+        .post("/api/tickets", json!({"title": "Ticket BBB"}))
+        .await?;
+    let res = client.get("/api/tickets?sort=-id&limit=1").await?;
+    assert_eq!(res.body["total"], 2);
+    assert_eq!(res.body["items"][0]["title"], "Ticket BBB");
+    assert_eq!(res.body["next_offset"], 1);
+
+    // -- Update
+    let res = client
+        .patch("/api/tickets/1", json!({"title": "Ticket AAA v2"}))
+        .await?;
+    assert_eq!(res.body["title"], "Ticket AAA v2");
+
+    // -- History (newest first, paged)
+    let res = client.get("/api/tickets/1/history?limit=1").await?;
+    assert_eq!(res.body["total"], 2);
+    assert_eq!(res.body["items"][0]["action"], "update");
+    assert_eq!(res.body["next_offset"], 1);
+    let res = client.get("/api/tickets/1/history?offset=1").await?;
+    assert_eq!(res.body["items"][0]["action"], "create");
+    assert_eq!(res.body["next_offset"], json!(null));
+
+    // -- Delete (to the trash)
+    let res = client.delete("/api/tickets/1").await?;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.body["deleted_at"].is_u64());
+    let res = client.get("/api/tickets/1").await?;
+    res.assert_error(StatusCode::NOT_FOUND, "ENTITY_NOT_FOUND");
+    let res = client.get("/api/tickets").await?;
+    assert_eq!(res.body["total"], 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_access_denied() -> Result<()> {
+    let mut demo1 = TestClient::new_logged_in("demo1").await?;
+    demo1
+        .post("/api/tickets", json!({"title": "demo1 only"}))
+        .await?;
+
+    let mut admin = demo1.new_session();
+    admin.login("admin", DEMO_PWD).await?;
+    let res = admin
+        .post("/api/tickets", json!({"title": "admin only"}))
+        .await?;
+    let admin_ticket_id = res.body["id"].as_u64().unwrap();
+
+    // demo1 does not see the admin ticket, the admin sees both.
+    let res = demo1
+        .get(&format!("/api/tickets/{admin_ticket_id}"))
+        .await?;
+    res.assert_error(StatusCode::FORBIDDEN, "ACCESS_DENIED");
+    let res = demo1.get("/api/tickets").await?;
+    assert_eq!(res.body["total"], 1);
+    let res = admin.get("/api/tickets").await?;
+    assert_eq!(res.body["total"], 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_transition_conflict() -> Result<()> {
+    let mut client = TestClient::new_logged_in("demo1").await?;
+    client.post("/api/tickets", json!({"title": "t"})).await?;
+
+    let res = client
+        .post("/api/tickets/1/transition", json!({"status": "closed"}))
+        .await?;
+    assert_eq!(res.body["status"], "closed");
+    let res = client
+        .post("/api/tickets/1/transition", json!({"status": "resolved"}))
+        .await?;
+    res.assert_error(StatusCode::CONFLICT, "INVALID_TRANSITION");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_comments() -> Result<()> {
+    // demo1 - ticket creator, demo2 - member the ticket is shared with.
+    let mut demo1 = TestClient::new_logged_in("demo1").await?;
+    let user_fc = UserForCreate {
+        username: "demo2".to_string(),
+        pwd_clear: DEMO_PWD.to_string(),
+    };
+    let demo2_id = UserBmc::create(&demo1.mc, user_fc).await?.id;
+    let mut demo2 = demo1.new_session();
+    demo2.login("demo2", DEMO_PWD).await?;
+    let mut admin = demo1.new_session();
+    admin.login("admin", DEMO_PWD).await?;
+
+    demo1.post("/api/tickets", json!({"title": "A"})).await?;
+    demo1.post("/api/tickets", json!({"title": "B"})).await?;
+    demo1
+        .post("/api/tickets/1/shares", json!({"user_id": demo2_id}))
+        .await?;
+    let by_demo2 = comment_ticket_1(&mut demo2, "by demo2").await?;
+    let by_demo1 = comment_ticket_1(&mut demo1, "by demo1").await?;
+
+    // -- Edit, by the author only (not even the ticket creator or an admin).
+    let uri = format!("/api/tickets/1/comments/{by_demo2}");
+    let res = demo1.patch(&uri, json!({"body": "v2"})).await?;
+    res.assert_error(StatusCode::FORBIDDEN, "ACCESS_DENIED");
+    let res = admin.patch(&uri, json!({"body": "v2"})).await?;
+    res.assert_error(StatusCode::FORBIDDEN, "ACCESS_DENIED");
+    let res = demo2.patch(&uri, json!({"body": "v2"})).await?;
+    assert_eq!(res.body["body"], "v2");
+
+    // -- The comment id of another (readable) ticket, even for the author.
+    let res = demo1
+        .patch(
+            &format!("/api/tickets/2/comments/{by_demo1}"),
+            json!({"body": "v3"}),
+        )
+        .await?;
+    res.assert_error(StatusCode::NOT_FOUND, "ENTITY_NOT_FOUND");
+    let res = demo1
+        .delete(&format!("/api/tickets/2/comments/{by_demo1}"))
+        .await?;
+    res.assert_error(StatusCode::NOT_FOUND, "ENTITY_NOT_FOUND");
+
+    // -- Delete, by the author, the ticket creator or an admin.
+    let res = demo2
+        .delete(&format!("/api/tickets/1/comments/{by_demo1}"))
+        .await?;
+    res.assert_error(StatusCode::FORBIDDEN, "ACCESS_DENIED");
+    let res = demo1.delete(&uri).await?;
+    assert_eq!(res.status, StatusCode::OK);
+    let own = comment_ticket_1(&mut demo2, "own").await?;
+    let res = demo2
+        .delete(&format!("/api/tickets/1/comments/{own}"))
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = admin
+        .delete(&format!("/api/tickets/1/comments/{by_demo1}"))
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = demo1.get("/api/tickets/1/comments").await?;
+    assert_eq!(res.body, json!([]));
+
+    // -- Purged with the ticket.
+    let last = comment_ticket_1(&mut demo2, "last").await?;
+    // Deleted long ago (past the retention).
+    demo1.store.ticket_soft_delete(1, 0).await?;
+    assert_eq!(demo1.mc.purge_trash(Duration::from_secs(60)).await?, 1);
+    assert!(demo1.store.comment_get(last).await?.is_none());
+    assert!(demo1.store.comment_list(1).await?.is_empty());
+
+    Ok(())
+}
+
+/// Returns the comment id.
+async fn comment_ticket_1(client: &mut TestClient, body: &str) -> Result<u64> {
+    let res = client
+        .post("/api/tickets/1/comments", json!({"body": body}))
+        .await?;
+    assert_eq!(res.status, StatusCode::OK, "body: {}", res.body);
+
+    Ok(res.body["id"].as_u64().unwrap())
+}
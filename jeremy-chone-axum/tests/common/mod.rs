@@ -0,0 +1,257 @@
+//! In-process test harness
+//! (the app router driven with tower `oneshot`, with a cookie jar like a browser)
+
+// Each test crate uses its own subset of the harness.
+#![allow(dead_code)]
+
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use jeremy_chone_axum::{
+    _dev_utils,
+    config::Config,
+    log::RequestLogger,
+    model::{
+        store::{MemStore, Store},
+        ModelController,
+    },
+    routes_all,
+    web::mw_metrics::Metrics,
+};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+/// Password of the seeded demo users.
+pub const DEMO_PWD: &str = "welcome";
+
+/// Peer address of the test requests (for the per client ip rate limits).
+const CLIENT_ADDR: ([u8; 4], u16) = ([127, 0, 0, 1], 40_000);
+
+/// One client on a fresh app (in-memory store, with the demo users).
+pub struct TestClient {
+    app: Router,
+    /// The app model controller and store (e.g., for the setups without a route).
+    pub mc: ModelController,
+    pub store: Arc<dyn Store>,
+    /// Cookie name -> value.
+    cookies: BTreeMap<String, String>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// `Value::Null` when the body is not json.
+    pub body: Value,
+    /// The raw body (lossy utf8).
+    pub text: String,
+}
+
+impl TestClient {
+    pub async fn new() -> Result<Self> {
+        Self::new_with_config(|_| {}).await
+    }
+
+    /// `new` with some config overrides (e.g., the web folder).
+    pub async fn new_with_config(f: impl FnOnce(&mut Config)) -> Result<Self> {
+        Self::new_with_store(Arc::new(MemStore::default()), f).await
+    }
+
+    /// `new_with_config` on another store (e.g., an in-memory SQLite `SqlStore`).
+    pub async fn new_with_store(
+        store: Arc<dyn Store>,
+        f: impl FnOnce(&mut Config),
+    ) -> Result<Self> {
+        // The cargo env config, with the demo users.
+        let mut config = Config::load()?;
+        config.db_url = None;
+        config.dev_seed_pwd = Some(DEMO_PWD.to_string());
+        f(&mut config);
+        let config = Arc::new(config);
+
+        let mc = ModelController::new_with_store(store.clone());
+        _dev_utils::init_dev(&mc, &config).await?;
+        let logger = RequestLogger::start(&[]).await?;
+
+        Ok(Self {
+            app: routes_all(config, mc.clone(), logger, Metrics::default()),
+            mc,
+            store,
+            cookies: BTreeMap::new(),
+        })
+    }
+
+    /// `new` and logged in as `username` (password `DEMO_PWD`).
+    pub async fn new_logged_in(username: &str) -> Result<Self> {
+        let mut client = Self::new().await?;
+        let res = client.login(username, DEMO_PWD).await?;
+        assert_eq!(res.status, StatusCode::OK, "login {username}: {}", res.body);
+
+        Ok(client)
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<TestResponse> {
+        let body = serde_json::json!({"username": username, "password": password});
+        self.post("/api/login", body).await
+    }
+
+    /// Another client (own cookie jar) on the same app.
+    pub fn new_session(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            mc: self.mc.clone(),
+            store: self.store.clone(),
+            cookies: BTreeMap::new(),
+        }
+    }
+
+    /// Serves the app on a local port (e.g., for the WebSocket clients), returns its address.
+    pub async fn serve(&self) -> Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = self
+            .app
+            .clone()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Ok(addr)
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies.get(name).map(String::as_str)
+    }
+
+    pub fn set_cookie(&mut self, name: &str, value: &str) {
+        self.cookies.insert(name.to_string(), value.to_string());
+    }
+
+    pub async fn get(&mut self, uri: &str) -> Result<TestResponse> {
+        self.send(Method::GET, uri, &[], None).await
+    }
+
+    /// `get` with extra request headers, e.g., `[("accept-encoding", "gzip")]`.
+    pub async fn get_with_headers(
+        &mut self,
+        uri: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<TestResponse> {
+        self.send(Method::GET, uri, headers, None).await
+    }
+
+    pub async fn post(&mut self, uri: &str, body: Value) -> Result<TestResponse> {
+        let body = serde_json::to_vec(&body)?;
+        self.send(Method::POST, uri, &[], Some(body)).await
+    }
+
+    /// `post` of a raw (possibly invalid) json body.
+    pub async fn post_raw(&mut self, uri: &str, body: &str) -> Result<TestResponse> {
+        let body = body.as_bytes().to_vec();
+        self.send(Method::POST, uri, &[], Some(body)).await
+    }
+
+    pub async fn patch(&mut self, uri: &str, body: Value) -> Result<TestResponse> {
+        let body = serde_json::to_vec(&body)?;
+        self.send(Method::PATCH, uri, &[], Some(body)).await
+    }
+
+    pub async fn delete(&mut self, uri: &str) -> Result<TestResponse> {
+        self.send(Method::DELETE, uri, &[], None).await
+    }
+
+    async fn send(
+        &mut self,
+        method: Method,
+        uri: &str,
+        headers: &[(&str, &str)],
+        body: Option<Vec<u8>>,
+    ) -> Result<TestResponse> {
+        let mut req = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        if !self.cookies.is_empty() {
+            let cookie = self
+                .cookies
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            req = req.header(header::COOKIE, cookie);
+        }
+        let body = match body {
+            Some(body) => {
+                req = req.header(header::CONTENT_TYPE, "application/json");
+                Body::from(body)
+            }
+            None => Body::empty(),
+        };
+        let mut req = req.body(body)?;
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(CLIENT_ADDR)));
+
+        let res = self.app.clone().oneshot(req).await?;
+
+        self.store_cookies(res.headers());
+        let status = res.status();
+        let headers = res.headers().clone();
+        let bytes = to_bytes(res.into_body(), usize::MAX).await?;
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+
+        Ok(TestResponse {
+            status,
+            headers,
+            body,
+            text,
+        })
+    }
+
+    /// Keeps the `Set-Cookie` values, drops the removed (empty or expired) ones.
+    fn store_cookies(&mut self, headers: &HeaderMap) {
+        for set_cookie in headers.get_all(header::SET_COOKIE) {
+            let Ok(set_cookie) = set_cookie.to_str() else {
+                continue;
+            };
+            let mut parts = set_cookie.split(';').map(str::trim);
+            let Some((name, value)) = parts.next().and_then(|nv| nv.split_once('=')) else {
+                continue;
+            };
+            let removed = value.is_empty() || parts.any(|p| p.eq_ignore_ascii_case("Max-Age=0"));
+
+            if removed {
+                self.cookies.remove(name);
+            } else {
+                self.cookies.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+}
+
+impl TestResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// The exact error body of `main_response_mapper` (`req_uuid` is the request id).
+    #[track_caller]
+    pub fn assert_error(&self, status: StatusCode, client_error: &str) {
+        let req_id = self.header("x-request-id").expect("x-request-id header");
+        assert_eq!(self.status, status, "body: {}", self.body);
+        assert_eq!(
+            self.body,
+            serde_json::json!({
+                "error": {
+                    "type": client_error,
+                    "req_uuid": req_id,
+                }
+            })
+        );
+    }
+}
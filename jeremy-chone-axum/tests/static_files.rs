@@ -0,0 +1,117 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use axum::http::StatusCode;
+
+use common::TestClient;
+
+/// `<tmp>/<name>/secret.txt` and the `<tmp>/<name>/web/` folder (the web folder).
+fn web_fixture(name: &str) -> Result<PathBuf> {
+    let root = std::env::temp_dir().join(format!("jca-static-{name}-{}", std::process::id()));
+    let web = root.join("web");
+    fs::create_dir_all(&web)?;
+
+    fs::write(root.join("secret.txt"), "top secret")?;
+    fs::write(web.join("index.html"), "<html>spa index</html>")?;
+    fs::write(web.join("app.js"), "console.log('app');")?;
+    fs::write(web.join("app.js.gz"), "gz-bytes")?;
+
+    Ok(web)
+}
+
+async fn client_for(name: &str) -> Result<TestClient> {
+    let web = web_fixture(name)?;
+    TestClient::new_with_config(|config| config.web_folder = web).await
+}
+
+#[tokio::test]
+async fn test_static_outside_root_unreachable() -> Result<()> {
+    let mut client = client_for("outside").await?;
+
+    for uri in [
+        "/../secret.txt",
+        "/%2e%2e/secret.txt",
+        "/..%2fsecret.txt",
+        "/sub/../../secret.txt",
+        "/..%5csecret.txt",
+    ] {
+        let res = client.get(uri).await?;
+        assert_ne!(res.status, StatusCode::OK, "{uri}");
+        assert!(!res.text.contains("top secret"), "{uri}");
+    }
+
+    // The default web folder does not expose the sources.
+    let mut client = TestClient::new().await?;
+    for uri in ["/Cargo.toml", "/src/main.rs", "/.cargo/config.toml"] {
+        let res = client.get(uri).await?;
+        assert_eq!(res.status, StatusCode::NOT_FOUND, "{uri}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_static_spa_fallback() -> Result<()> {
+    let mut client = client_for("spa").await?;
+
+    let res = client.get("/tickets/42").await?;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.text, "<html>spa index</html>");
+    assert_eq!(res.header("cache-control"), Some("no-cache"));
+
+    // A missing asset is not the index.
+    let res = client.get("/missing.js").await?;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_static_cache_headers() -> Result<()> {
+    let mut client = client_for("cache").await?;
+
+    let res = client.get("/app.js").await?;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.header("cache-control"), Some("public, max-age=3600"));
+    assert!(res.header("last-modified").is_some());
+    let etag = res.header("etag").expect("etag").to_string();
+    assert!(etag.starts_with("W/\""));
+
+    let res = client
+        .get_with_headers("/app.js", &[("if-none-match", &etag)])
+        .await?;
+    assert_eq!(res.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(res.header("etag"), Some(etag.as_str()));
+    assert!(res.text.is_empty());
+
+    let res = client
+        .get_with_headers("/app.js", &[("if-none-match", "W/\"other\"")])
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_static_precompressed() -> Result<()> {
+    let mut client = client_for("precompressed").await?;
+
+    let res = client
+        .get_with_headers("/app.js", &[("accept-encoding", "gzip")])
+        .await?;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.header("content-encoding"), Some("gzip"));
+    assert_eq!(res.header("vary"), Some("accept-encoding"));
+    assert_eq!(res.text, "gz-bytes");
+
+    // No `.br` sibling, the plain file.
+    let res = client
+        .get_with_headers("/app.js", &[("accept-encoding", "br")])
+        .await?;
+    assert_eq!(res.header("content-encoding"), None);
+    assert_eq!(res.text, "console.log('app');");
+
+    Ok(())
+}
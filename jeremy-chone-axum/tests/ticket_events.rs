@@ -0,0 +1,75 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use axum::http::{header, StatusCode};
+use futures_util::{SinkExt, StreamExt};
+use jeremy_chone_axum::{
+    model::{
+        store::SqlStore,
+        user::{UserBmc, UserForCreate},
+    },
+    web::AUTH_TOKEN,
+};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+use common::{TestClient, DEMO_PWD};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ticket_events_ws_with_client_frames() -> Result<()> {
+    // SQL store, so the visibility check of the shared ticket really awaits.
+    let store = Arc::new(SqlStore::connect("sqlite::memory:").await?);
+    let mut demo1 = TestClient::new_with_store(store, |_| {}).await?;
+    demo1.login("demo1", DEMO_PWD).await?;
+
+    // demo1 - ticket creator, demo2 - member the ticket is shared with.
+    let user_fc = UserForCreate {
+        username: "demo2".to_string(),
+        pwd_clear: DEMO_PWD.to_string(),
+    };
+    let demo2_id = UserBmc::create(&demo1.mc, user_fc).await?.id;
+    demo1.post("/api/tickets", json!({"title": "v"})).await?;
+    demo1
+        .post("/api/tickets/1/shares", json!({"user_id": demo2_id}))
+        .await?;
+    let mut demo2 = demo1.new_session();
+    let res = demo2.login("demo2", DEMO_PWD).await?;
+    assert_eq!(res.status, StatusCode::OK);
+
+    // -- Subscribe (demo2)
+    let addr = demo1.serve().await?;
+    let mut req = format!("ws://{addr}/api/tickets/events/ws").into_client_request()?;
+    let cookie = format!("{AUTH_TOKEN}={}", demo2.cookie(AUTH_TOKEN).unwrap());
+    req.headers_mut().insert(header::COOKIE, cookie.parse()?);
+    let (mut ws, _) = tokio_tungstenite::connect_async(req).await?;
+
+    // -- Publish (demo1), while demo2 sends frames.
+    const COUNT: usize = 50;
+    for i in 0..COUNT {
+        let res = demo1
+            .patch("/api/tickets/1", json!({"title": format!("v{i}")}))
+            .await?;
+        assert_eq!(res.status, StatusCode::OK, "patch {i}: {}", res.body);
+        ws.send(Message::Text("ignored".to_string())).await?;
+        ws.send(Message::Ping(vec![])).await?;
+    }
+
+    // -- Check, every event in order.
+    let mut titles = Vec::new();
+    while titles.len() < COUNT {
+        let msg = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await?
+            .expect("socket open")?;
+        if let Message::Text(text) = msg {
+            let event: Value = serde_json::from_str(&text)?;
+            assert_eq!(event["kind"], "updated");
+            titles.push(event["ticket"]["title"].as_str().unwrap().to_string());
+        }
+    }
+    let expected: Vec<_> = (0..COUNT).map(|i| format!("v{i}")).collect();
+    assert_eq!(titles, expected);
+
+    Ok(())
+}
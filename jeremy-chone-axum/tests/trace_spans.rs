@@ -0,0 +1,78 @@
+mod common;
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use common::TestClient;
+
+/// The json trace lines, in memory.
+#[derive(Clone, Default)]
+struct CaptureWriter(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for CaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CaptureWriter {
+    fn lines(&self) -> Vec<Value> {
+        let buf = self.0.lock().unwrap();
+        String::from_utf8_lossy(&buf)
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+/// Current thread test runtime, so the default subscriber sees the whole request.
+#[tokio::test]
+async fn test_trace_request_span() -> Result<()> {
+    let writer = CaptureWriter::default();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_env_filter("debug")
+        .with_writer({
+            let writer = writer.clone();
+            move || writer.clone()
+        })
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut client = TestClient::new_logged_in("demo1").await?;
+    let res = client.get("/api/tickets?limit=1").await?;
+    let req_id = res.header("x-request-id").expect("x-request-id");
+
+    let lines = writer.lines();
+    let handler_line = lines
+        .iter()
+        .find(|line| {
+            line["span"]["req_id"] == req_id
+                && line["fields"]["message"]
+                    .as_str()
+                    .is_some_and(|m| m.ends_with("- list_tickets"))
+        })
+        .expect("list_tickets event in the request span");
+
+    let span = &handler_line["span"];
+    assert_eq!(span["name"], "request");
+    assert_eq!(span["method"], "GET");
+    assert_eq!(span["uri"], "/api/tickets?limit=1");
+    assert_eq!(span["route"], "/api/tickets");
+    assert_eq!(span["user_id"], json!(1));
+    assert_eq!(handler_line["level"], "DEBUG");
+
+    Ok(())
+}